@@ -0,0 +1,50 @@
+use crate::data_codecs::DataToDataCodec;
+use crate::typ::DataAndMnemonic;
+use pyo3::PyResult;
+use std::io::{Read, Write};
+use tracing::instrument;
+
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_WINDOW_SIZE: u32 = 22;
+
+pub(crate) struct BrotliCodec {
+    quality: u32,
+}
+
+impl BrotliCodec {
+    pub fn new_default() -> Self {
+        BrotliCodec { quality: 9 }
+    }
+    pub fn new(quality: u32) -> Self {
+        BrotliCodec { quality }
+    }
+}
+
+impl DataToDataCodec for BrotliCodec {
+    #[instrument(name = "brotli_encode", skip_all)]
+    fn encode(&self, data: &[u8]) -> PyResult<DataAndMnemonic> {
+        let mut compressed = Vec::with_capacity(data.len() / 2);
+        {
+            let mut wtr = brotli::CompressorWriter::new(
+                &mut compressed,
+                BROTLI_BUFFER_SIZE,
+                self.quality,
+                BROTLI_WINDOW_SIZE,
+            );
+            wtr.write_all(data)?;
+        }
+        Ok(DataAndMnemonic {
+            data: compressed,
+            codec: Self::MNEMONIC,
+        })
+    }
+
+    #[instrument(name = "brotli_decode", skip_all)]
+    fn decode(&self, data: &[u8]) -> PyResult<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        brotli::Decompressor::new(data, BROTLI_BUFFER_SIZE).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+
+    const MNEMONIC: u8 = b'r';
+}
@@ -0,0 +1,46 @@
+use crate::data_codecs::DataToDataCodec;
+use crate::typ::DataAndMnemonic;
+use bzip2::Compression;
+use bzip2::write::{BzDecoder, BzEncoder};
+use pyo3::PyResult;
+use std::io::Write;
+use tracing::instrument;
+
+pub(crate) struct Bzip2Codec {
+    level: u32,
+}
+
+impl Bzip2Codec {
+    pub fn new_default() -> Self {
+        Bzip2Codec { level: 6 }
+    }
+    pub fn new(level: u32) -> Self {
+        Bzip2Codec { level }
+    }
+}
+
+impl DataToDataCodec for Bzip2Codec {
+    #[instrument(name = "bzip2_encode", skip_all)]
+    fn encode(&self, data: &[u8]) -> PyResult<DataAndMnemonic> {
+        let mut wtr = BzEncoder::new(
+            Vec::with_capacity(data.len() / 2),
+            Compression::new(self.level),
+        );
+        wtr.write_all(data)?;
+        let compressed = wtr.finish()?;
+        Ok(DataAndMnemonic {
+            data: compressed,
+            codec: Self::MNEMONIC,
+        })
+    }
+
+    #[instrument(name = "bzip2_decode", skip_all)]
+    fn decode(&self, data: &[u8]) -> PyResult<Vec<u8>> {
+        let mut wtr = BzDecoder::new(Vec::new());
+        wtr.write_all(data)?;
+        let decompressed = wtr.finish()?;
+        Ok(decompressed)
+    }
+
+    const MNEMONIC: u8 = b'b';
+}
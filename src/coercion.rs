@@ -0,0 +1,169 @@
+use crate::utils::to_talsi_error;
+use pyo3::prelude::*;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyBytes, PyString};
+use pyo3::{Bound, Py, PyAny, PyResult, Python};
+use std::str::FromStr;
+
+/// Requested output type for `get_as`/`get_many_as`, mirroring the `Conversion` type-coercion
+/// idea from Vector's config parser: a value is decoded normally through its stored codec chain
+/// and then reinterpreted as this concrete Python type, rather than trusting the codec mnemonic
+/// alone to produce the shape the caller wants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ValueKind {
+    Bytes,
+    Int,
+    Float,
+    Bool,
+    Str,
+    Timestamp,
+}
+
+impl FromStr for ValueKind {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "bytes" => Ok(Self::Bytes),
+            "int" => Ok(Self::Int),
+            "float" => Ok(Self::Float),
+            "bool" => Ok(Self::Bool),
+            "str" => Ok(Self::Str),
+            "timestamp" => Ok(Self::Timestamp),
+            other => Err(to_talsi_error(format!(
+                "Unknown coercion kind {:?}; expected one of bytes, int, float, bool, str, timestamp",
+                other
+            ))),
+        }
+    }
+}
+
+impl ValueKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Bytes => "bytes",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+            Self::Str => "str",
+            Self::Timestamp => "timestamp",
+        }
+    }
+}
+
+fn mismatch(kind: ValueKind, value: &Bound<PyAny>) -> PyErr {
+    let found = value
+        .get_type()
+        .name()
+        .and_then(|n| n.extract::<String>())
+        .unwrap_or_else(|_| "?".to_string());
+    let expected = match kind {
+        ValueKind::Bytes | ValueKind::Str => "bytes or str",
+        _ => "bytes, str or a number",
+    };
+    to_talsi_error(format!(
+        "Can't coerce to {}: expected {}, found {}",
+        kind.name(),
+        expected,
+        found
+    ))
+}
+
+/// Extracts `value` as a UTF-8 `str`, decoding it from bytes first if necessary, for use as the
+/// common starting point of the textual coercions below.
+fn as_text(kind: ValueKind, value: &Bound<PyAny>) -> PyResult<String> {
+    if let Ok(s) = value.extract::<String>() {
+        return Ok(s);
+    }
+    if let Ok(b) = value.extract::<Vec<u8>>() {
+        return String::from_utf8(b).map_err(|e| to_talsi_error(e.to_string()));
+    }
+    Err(mismatch(kind, value))
+}
+
+/// Looks up `datetime.datetime`, since talsi has no date/time crate of its own and the stdlib
+/// already parses the formats callers are likely to have written (ISO 8601 strings, or bare
+/// epoch seconds).
+fn datetime_class(py: Python<'_>) -> PyResult<&Bound<'_, PyAny>> {
+    static DATETIME_CLASS: PyOnceLock<Py<PyAny>> = PyOnceLock::new();
+    DATETIME_CLASS.import(py, "datetime", "datetime")
+}
+
+/// Reinterprets `value` (already decoded from its stored codec chain via `into_python`) as
+/// `kind`, mirroring `FromStr for Conversion`: bytes/str are parsed, and numeric/bool values are
+/// converted to the numeric, bool and timestamp kinds rather than merely type-checked. The
+/// `bytes` and `str` kinds accept only bytes or str values. Returns a `TalsiError` naming the
+/// expected and actual types on failure.
+pub(crate) fn coerce_value<'py>(
+    py: Python<'py>,
+    value: Bound<'py, PyAny>,
+    kind: ValueKind,
+) -> PyResult<Bound<'py, PyAny>> {
+    match kind {
+        ValueKind::Bytes => {
+            if let Ok(b) = value.extract::<Vec<u8>>() {
+                return Ok(PyBytes::new(py, &b).into_any());
+            }
+            if let Ok(s) = value.extract::<String>() {
+                return Ok(PyBytes::new(py, s.as_bytes()).into_any());
+            }
+            Err(mismatch(kind, &value))
+        }
+        ValueKind::Str => {
+            if let Ok(s) = value.extract::<String>() {
+                return Ok(PyString::new(py, &s).into_any());
+            }
+            if let Ok(b) = value.extract::<Vec<u8>>() {
+                let s = String::from_utf8(b).map_err(|e| to_talsi_error(e.to_string()))?;
+                return Ok(PyString::new(py, &s).into_any());
+            }
+            Err(mismatch(kind, &value))
+        }
+        ValueKind::Int => {
+            if let Ok(i) = value.extract::<i64>() {
+                return Ok(i.into_pyobject(py)?.into_any());
+            }
+            if let Ok(f) = value.extract::<f64>() {
+                return Ok((f as i64).into_pyobject(py)?.into_any());
+            }
+            let text = as_text(kind, &value)?;
+            let i: i64 = text.trim().parse().map_err(|_| mismatch(kind, &value))?;
+            Ok(i.into_pyobject(py)?.into_any())
+        }
+        ValueKind::Float => {
+            if let Ok(f) = value.extract::<f64>() {
+                return Ok(f.into_pyobject(py)?.into_any());
+            }
+            let text = as_text(kind, &value)?;
+            let f: f64 = text.trim().parse().map_err(|_| mismatch(kind, &value))?;
+            Ok(f.into_pyobject(py)?.into_any())
+        }
+        ValueKind::Bool => {
+            if let Ok(b) = value.extract::<bool>() {
+                return Ok(b.into_pyobject(py)?.to_owned().into_any());
+            }
+            if let Ok(i) = value.extract::<i64>() {
+                return Ok((i != 0).into_pyobject(py)?.to_owned().into_any());
+            }
+            if let Ok(f) = value.extract::<f64>() {
+                return Ok((f != 0.0).into_pyobject(py)?.to_owned().into_any());
+            }
+            let text = as_text(kind, &value)?;
+            match text.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "y" | "on" => Ok(true.into_pyobject(py)?.to_owned().into_any()),
+                "false" | "0" | "no" | "n" | "off" => Ok(false.into_pyobject(py)?.to_owned().into_any()),
+                _ => Err(mismatch(kind, &value)),
+            }
+        }
+        ValueKind::Timestamp => {
+            let class = datetime_class(py)?;
+            if let Ok(f) = value.extract::<f64>() {
+                return class.call_method1("fromtimestamp", (f,));
+            }
+            let text = as_text(kind, &value)?;
+            class
+                .call_method1("fromisoformat", (text,))
+                .map_err(|_| mismatch(kind, &value))
+        }
+    }
+}
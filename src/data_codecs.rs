@@ -1,44 +1,282 @@
-use crate::typ::DataAndMnemonic;
+use crate::bzip2_codec::Bzip2Codec;
+use crate::brotli_codec::BrotliCodec;
+use crate::gzip_codec::GzipCodec;
+use crate::lz4_codec::Lz4Codec;
+use crate::shuffle_codec::{BitShuffleCodec, ShuffleCodec};
+use crate::snappy_codec::SnappyCodec;
+use crate::typ::{DataAndMnemonic, DataAndMnemonics};
+use crate::xz_codec::XzCodec;
+use crate::zstd_codec::ZstdCodec;
 use pyo3::exceptions::PyValueError;
 use pyo3::{PyErr, PyResult};
-use std::io::{Read, Write};
+use std::sync::Arc;
 use tracing::instrument;
 
-trait DataToDataCodec {
+pub(crate) trait DataToDataCodec {
     fn encode(&self, data: &[u8]) -> PyResult<DataAndMnemonic>;
     fn decode(&self, data: &[u8]) -> PyResult<Vec<u8>>;
     const MNEMONIC: u8;
 }
-struct SnappyCodec;
-impl DataToDataCodec for SnappyCodec {
-    #[instrument(name = "snappy_encode", skip_all)]
-    fn encode(&self, data: &[u8]) -> PyResult<DataAndMnemonic> {
-        let mut wtr = snap::write::FrameEncoder::new(Vec::with_capacity(data.len() / 2));
-        wtr.write_all(data)?;
-        let compressed = wtr.into_inner().unwrap();
-        Ok(DataAndMnemonic {
-            data: compressed,
-            codec: Self::MNEMONIC,
+
+/// Below this size, compression overhead isn't worth it, so values are stored as-is.
+const MIN_COMPRESSIBLE_LEN: usize = 1024;
+
+/// How much of the payload `auto` mode samples to estimate compressibility.
+const ENTROPY_SAMPLE_LEN: usize = 4096;
+
+/// Above this many bits of Shannon entropy per byte (out of a possible 8), data is assumed
+/// already-compressed or otherwise incompressible, so `auto` mode skips running the
+/// candidate codecs against it rather than paying for CPU that won't pay off.
+const ENTROPY_SKIP_THRESHOLD: f64 = 7.5;
+
+/// Cheap estimate of the Shannon entropy (bits/byte) of a sample of `data`, used to
+/// decide whether compression is likely worth attempting at all.
+fn estimate_entropy_bits_per_byte(data: &[u8]) -> f64 {
+    let sample = &data[..data.len().min(ENTROPY_SAMPLE_LEN)];
+    let mut counts = [0u32; 256];
+    for &b in sample {
+        counts[b as usize] += 1;
+    }
+    let len = sample.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .fold(0.0, |acc, &c| {
+            let p = c as f64 / len;
+            acc - p * p.log2()
         })
+}
+
+/// Which data codec `Storage` applies to values before they're written, and at what level.
+///
+/// `Default` preserves the historical behavior: Snappy above the size threshold, nothing below it.
+#[derive(Clone)]
+pub(crate) enum DataCodecChoice {
+    Default,
+    None,
+    Snappy,
+    Zstd { level: i32 },
+    Lz4,
+    Gzip { level: u32 },
+    Xz { level: u32 },
+    Bzip2 { level: u32 },
+    Brotli { level: u32 },
+    /// Compresses with `candidates` (defaulting to snappy/lz4/zstd) and keeps whichever output
+    /// is smallest, falling back to storing the value uncompressed if nothing beats the raw size.
+    Auto { zstd_level: i32, candidates: Vec<AutoCandidate> },
+    /// Zstd compression against a dictionary trained with `Storage::train_zstd_dictionary`.
+    /// The dictionary bytes travel with the choice so `get_best_data_encoding` doesn't need
+    /// any storage access of its own; `dict_id` is recorded in the frame header for decoding.
+    ZstdDict { level: i32, dict_id: u32, dict: Arc<Vec<u8>> },
+    /// Byte-shuffles fixed-width numeric elements before compressing with zstd, so the stored
+    /// mnemonic chain is `[shuffle, zstd]`. `element_width` is the element size in bytes
+    /// (e.g. 4 for `i32`/`f32`, 8 for `i64`/`f64`).
+    Shuffle { element_width: u8, level: i32 },
+    /// Same idea as `Shuffle`, but permutes at bit rather than byte granularity.
+    BitShuffle { element_width: u8, level: i32 },
+}
+
+/// One codec `auto` mode may try; see `get_best_data_encoding_auto`.
+#[derive(Clone, Copy)]
+pub(crate) enum AutoCandidate {
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+impl AutoCandidate {
+    fn encode(self, data: &[u8], zstd_level: i32) -> PyResult<DataAndMnemonic> {
+        match self {
+            Self::Snappy => SnappyCodec.encode(data),
+            Self::Lz4 => Lz4Codec.encode(data),
+            Self::Zstd => ZstdCodec::new(zstd_level).encode(data),
+        }
     }
 
-    #[instrument(name = "snappy_decode", skip_all)]
-    fn decode(&self, data: &[u8]) -> PyResult<Vec<u8>> {
-        let mut rdr = snap::read::FrameDecoder::new(data);
-        let mut decompressed = Vec::new();
-        rdr.read_to_end(&mut decompressed)?;
-        Ok(decompressed)
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "snappy" => Ok(Self::Snappy),
+            "lz4" => Ok(Self::Lz4),
+            "zstd" => Ok(Self::Zstd),
+            other => Err(format!("Unknown auto candidate codec: {}", other)),
+        }
     }
+}
 
-    const MNEMONIC: u8 = b's';
+/// `auto`'s candidate set when none is given explicitly via `"auto:..."`.
+fn default_auto_candidates() -> Vec<AutoCandidate> {
+    vec![AutoCandidate::Snappy, AutoCandidate::Lz4, AutoCandidate::Zstd]
+}
+
+impl DataCodecChoice {
+    /// Parses a user-facing codec name (and optional level) into a `DataCodecChoice`,
+    /// applying each codec's usual default level when none is given. `element_width` is
+    /// only meaningful for `shuffle`/`bitshuffle`, where it defaults to 4 bytes (`i32`/`f32`).
+    ///
+    /// `auto` accepts an optional `"auto:snappy,zstd"`-style suffix naming a restricted or
+    /// extended candidate set; bare `"auto"` uses `default_auto_candidates`. Either way, `level`
+    /// (when given) sets the zstd level `auto` compresses its zstd candidate at.
+    pub fn parse(name: &str, level: Option<i32>, element_width: Option<u8>) -> Result<Self, String> {
+        if let Some(rest) = name.strip_prefix("auto:") {
+            let candidates = rest
+                .split(',')
+                .map(AutoCandidate::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+            if candidates.is_empty() {
+                return Err("auto: candidate set must not be empty".to_string());
+            }
+            return Ok(Self::Auto {
+                zstd_level: level.unwrap_or(3),
+                candidates,
+            });
+        }
+        match name {
+            "default" => Ok(Self::Default),
+            "none" => Ok(Self::None),
+            "snappy" => Ok(Self::Snappy),
+            "zstd" => Ok(Self::Zstd {
+                level: level.unwrap_or(3),
+            }),
+            "lz4" => Ok(Self::Lz4),
+            "gzip" => Ok(Self::Gzip {
+                level: level.unwrap_or(6) as u32,
+            }),
+            "xz" => Ok(Self::Xz {
+                level: level.unwrap_or(6) as u32,
+            }),
+            "bzip2" => Ok(Self::Bzip2 {
+                level: level.unwrap_or(6) as u32,
+            }),
+            "brotli" => Ok(Self::Brotli {
+                level: level.unwrap_or(9) as u32,
+            }),
+            "auto" => Ok(Self::Auto {
+                zstd_level: level.unwrap_or(3),
+                candidates: default_auto_candidates(),
+            }),
+            "shuffle" => Ok(Self::Shuffle {
+                element_width: element_width.unwrap_or(4),
+                level: level.unwrap_or(3),
+            }),
+            "bitshuffle" => Ok(Self::BitShuffle {
+                element_width: element_width.unwrap_or(4),
+                level: level.unwrap_or(3),
+            }),
+            other => Err(format!("Unknown data codec: {}", other)),
+        }
+    }
 }
 
 #[instrument(skip_all)]
-pub fn get_best_data_encoding(data: &[u8]) -> PyResult<Option<DataAndMnemonic>> {
-    if data.len() >= 1024 {
-        return SnappyCodec.encode(data).map(Some);
+pub fn get_best_data_encoding(
+    data: &[u8],
+    choice: DataCodecChoice,
+) -> PyResult<Option<DataAndMnemonics>> {
+    match choice {
+        DataCodecChoice::Default => {
+            if data.len() < MIN_COMPRESSIBLE_LEN {
+                return Ok(None);
+            }
+            SnappyCodec.encode(data).map(DataAndMnemonics::from_single).map(Some)
+        }
+        // The size gate only protects `Default`/`Auto` from paying compression overhead nobody
+        // asked for; a codec named explicitly (including `ZstdDict`, whose whole point is small,
+        // similar records) always runs.
+        DataCodecChoice::Snappy => {
+            SnappyCodec.encode(data).map(DataAndMnemonics::from_single).map(Some)
+        }
+        DataCodecChoice::None => Ok(None),
+        DataCodecChoice::Zstd { level } => ZstdCodec::new(level)
+            .encode(data)
+            .map(DataAndMnemonics::from_single)
+            .map(Some),
+        DataCodecChoice::Lz4 => Lz4Codec.encode(data).map(DataAndMnemonics::from_single).map(Some),
+        DataCodecChoice::Gzip { level } => GzipCodec::new(level)
+            .encode(data)
+            .map(DataAndMnemonics::from_single)
+            .map(Some),
+        DataCodecChoice::Xz { level } => XzCodec::new(level)
+            .encode(data)
+            .map(DataAndMnemonics::from_single)
+            .map(Some),
+        DataCodecChoice::Bzip2 { level } => Bzip2Codec::new(level)
+            .encode(data)
+            .map(DataAndMnemonics::from_single)
+            .map(Some),
+        DataCodecChoice::Brotli { level } => BrotliCodec::new(level)
+            .encode(data)
+            .map(DataAndMnemonics::from_single)
+            .map(Some),
+        DataCodecChoice::Auto { zstd_level, candidates } => {
+            if data.len() < MIN_COMPRESSIBLE_LEN {
+                return Ok(None);
+            }
+            get_best_data_encoding_auto(data, zstd_level, &candidates)
+                .map(|maybe| maybe.map(DataAndMnemonics::from_single))
+        }
+        DataCodecChoice::ZstdDict {
+            level,
+            dict_id,
+            dict,
+        } => crate::zstd_codec::encode_with_dictionary(data, level, dict_id, &dict)
+            .map(DataAndMnemonics::from_single)
+            .map(Some),
+        DataCodecChoice::Shuffle {
+            element_width,
+            level,
+        } => encode_shuffle_then_zstd(data, element_width, false, level).map(Some),
+        DataCodecChoice::BitShuffle {
+            element_width,
+            level,
+        } => encode_shuffle_then_zstd(data, element_width, true, level).map(Some),
+    }
+}
+
+/// Runs the byte or bit shuffle filter over `data`, then zstd-compresses the shuffled bytes,
+/// producing the two-mnemonic chain `[shuffle, zstd]` (applied in that order, so decoded in
+/// reverse: zstd first, then the inverse shuffle).
+fn encode_shuffle_then_zstd(
+    data: &[u8],
+    element_width: u8,
+    bit: bool,
+    level: i32,
+) -> PyResult<DataAndMnemonics> {
+    let shuffled = if bit {
+        BitShuffleCodec::new(element_width).encode(data)?
+    } else {
+        ShuffleCodec::new(element_width).encode(data)?
+    };
+    let compressed = ZstdCodec::new(level).encode(&shuffled.data)?;
+    Ok(DataAndMnemonics::from_two(
+        compressed.data,
+        shuffled.codec,
+        compressed.codec,
+    ))
+}
+
+/// Compresses `data` with each candidate codec and keeps the smallest result, mirroring
+/// HTTP content-encoding negotiation except the winner is picked empirically by output
+/// size rather than by a fixed quality/cost ranking.
+fn get_best_data_encoding_auto(
+    data: &[u8],
+    zstd_level: i32,
+    candidates: &[AutoCandidate],
+) -> PyResult<Option<DataAndMnemonic>> {
+    if estimate_entropy_bits_per_byte(data) >= ENTROPY_SKIP_THRESHOLD {
+        return Ok(None);
+    }
+    let best = candidates
+        .iter()
+        .map(|c| c.encode(data, zstd_level))
+        .collect::<PyResult<Vec<_>>>()?
+        .into_iter()
+        .min_by_key(|c| c.data.len())
+        .expect("candidates is non-empty, checked in `DataCodecChoice::parse`");
+    if best.data.len() < data.len() {
+        Ok(Some(best))
+    } else {
+        Ok(None)
     }
-    Ok(None)
 }
 
 pub fn decode_from_data_and_mnemonic(data_and_mnemonic: DataAndMnemonic) -> PyResult<Vec<u8>> {
@@ -48,6 +286,14 @@ pub fn decode_from_data_and_mnemonic(data_and_mnemonic: DataAndMnemonic) -> PyRe
     } = data_and_mnemonic;
     match mnemonic {
         SnappyCodec::MNEMONIC => SnappyCodec.decode(&data),
+        ZstdCodec::MNEMONIC => ZstdCodec::new_default().decode(&data),
+        Lz4Codec::MNEMONIC => Lz4Codec.decode(&data),
+        GzipCodec::MNEMONIC => GzipCodec::new_default().decode(&data),
+        XzCodec::MNEMONIC => XzCodec::new_default().decode(&data),
+        Bzip2Codec::MNEMONIC => Bzip2Codec::new_default().decode(&data),
+        BrotliCodec::MNEMONIC => BrotliCodec::new_default().decode(&data),
+        ShuffleCodec::MNEMONIC => ShuffleCodec::new(0).decode(&data),
+        BitShuffleCodec::MNEMONIC => BitShuffleCodec::new(0).decode(&data),
         _ => Err(PyErr::new::<PyValueError, _>(format!(
             "Unknown data encoding mnemonic: {}",
             { mnemonic }
@@ -0,0 +1,46 @@
+use crate::data_codecs::DataToDataCodec;
+use crate::typ::DataAndMnemonic;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use pyo3::PyResult;
+use std::io::{Read, Write};
+use tracing::instrument;
+
+pub(crate) struct GzipCodec {
+    level: u32,
+}
+
+impl GzipCodec {
+    pub fn new_default() -> Self {
+        GzipCodec { level: 6 }
+    }
+    pub fn new(level: u32) -> Self {
+        GzipCodec { level }
+    }
+}
+
+impl DataToDataCodec for GzipCodec {
+    #[instrument(name = "gzip_encode", skip_all)]
+    fn encode(&self, data: &[u8]) -> PyResult<DataAndMnemonic> {
+        let mut wtr = GzEncoder::new(
+            Vec::with_capacity(data.len() / 2),
+            Compression::new(self.level),
+        );
+        wtr.write_all(data)?;
+        let compressed = wtr.finish()?;
+        Ok(DataAndMnemonic {
+            data: compressed,
+            codec: Self::MNEMONIC,
+        })
+    }
+
+    #[instrument(name = "gzip_decode", skip_all)]
+    fn decode(&self, data: &[u8]) -> PyResult<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(data).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+
+    const MNEMONIC: u8 = b'g';
+}
@@ -1,7 +1,17 @@
+mod brotli_codec;
+mod bzip2_codec;
+mod coercion;
 mod data_codecs;
+mod gzip_codec;
+mod lz4_codec;
 mod py_codecs;
+mod shuffle_codec;
+mod snappy_codec;
 mod storage;
 mod typ;
+mod utils;
+mod xz_codec;
+mod zstd_codec;
 
 #[cfg(feature = "tracing")]
 use tracing_subscriber;
@@ -0,0 +1,31 @@
+use crate::data_codecs::DataToDataCodec;
+use crate::typ::DataAndMnemonic;
+use crate::utils::to_talsi_error;
+use pyo3::PyResult;
+use std::io::{Read, Write};
+use tracing::instrument;
+
+pub(crate) struct Lz4Codec;
+
+impl DataToDataCodec for Lz4Codec {
+    #[instrument(name = "lz4_encode", skip_all)]
+    fn encode(&self, data: &[u8]) -> PyResult<DataAndMnemonic> {
+        let mut wtr = lz4_flex::frame::FrameEncoder::new(Vec::with_capacity(data.len() / 2));
+        wtr.write_all(data)?;
+        let compressed = wtr.finish().map_err(to_talsi_error)?;
+        Ok(DataAndMnemonic {
+            data: compressed,
+            codec: Self::MNEMONIC,
+        })
+    }
+
+    #[instrument(name = "lz4_decode", skip_all)]
+    fn decode(&self, data: &[u8]) -> PyResult<Vec<u8>> {
+        let mut rdr = lz4_flex::frame::FrameDecoder::new(data);
+        let mut decompressed = Vec::new();
+        rdr.read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+
+    const MNEMONIC: u8 = b'4';
+}
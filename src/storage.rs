@@ -1,21 +1,27 @@
-use crate::TalsiError;
-use crate::data_codecs::{decode_from_data_and_mnemonic, get_best_data_encoding};
+use crate::coercion::{coerce_value, ValueKind};
+use crate::data_codecs::{DataCodecChoice, decode_from_data_and_mnemonic, get_best_data_encoding};
 use crate::py_codecs::{decode_to_python_from_data_and_mnemonic, get_best_py_encoding};
-use crate::typ::{CodecsBlob, DataAndMnemonic, DataAndMnemonics, StringOrByteString};
+use crate::typ::{
+    decode_codecs_blob, encode_codecs_blob, read_varint, write_varint, CodecsBlob, DataAndMnemonic,
+    DataAndMnemonics, StringOrByteString,
+};
+use crate::utils::to_talsi_error;
 use either::Either;
 use eyre::Context;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyFrozenSet};
-use pyo3::{Bound, Py, PyAny, PyErr, PyObject, PyResult, Python, pyclass, pymethods};
+use pyo3::types::{PyBytes, PyDict, PyFrozenSet, PyList};
+use pyo3::{Bound, Py, PyAny, PyErr, PyResult, Python, pyclass, pymethods};
 use rayon::prelude::*;
 use rusqlite::limits::Limit;
 use rusqlite::types::ValueRef;
 use rusqlite::{Connection, params};
 use rusqlite::{OptionalExtension, params_from_iter};
 use std::borrow::Cow;
-use std::collections::HashSet;
-use std::sync::{Mutex, RwLock};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::instrument;
 
 type CowStr = Cow<'static, str>;
@@ -38,8 +44,31 @@ fn strings_or_bytestrings_as_strings(sobses: Vec<StringOrByteString>) -> eyre::R
     Ok(res)
 }
 
+fn now_ms() -> PyResult<i64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .map_err(to_talsi_error)
+}
+
+/// Converts a `decode_codecs_blob` failure (a truncated varint, or any other malformed header)
+/// into a `rusqlite::Error` so it can propagate as a normal row-decode error from inside a
+/// `query_row`/`query_map` closure instead of panicking across the FFI boundary; the call site's
+/// `.map_err(to_talsi_error)` turns it back into a `TalsiError` for the caller.
+fn corrupt_codecs_blob(e: PyErr) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        0,
+        rusqlite::types::Type::Blob,
+        Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())),
+    )
+}
+
 struct StorageSettings {
     pub allow_pickle: bool,
+    pub data_codec: RwLock<DataCodecChoice>,
+    // None disables auto-purge; `set`/`set_many` otherwise opportunistically sweep expired
+    // rows once at least this many milliseconds have passed since the last sweep.
+    pub auto_purge_interval_ms: Option<u64>,
 }
 
 #[pyclass(subclass, module = "talsi._talsi")]
@@ -48,12 +77,137 @@ pub struct Storage {
     known_namespaces: RwLock<HashSet<CowStr>>,
     settings: StorageSettings,
     max_num_binds: usize,
+    // Cache of trained zstd dictionaries by id, lazily loaded from `tl__zstd_dictionaries`.
+    zstd_dicts: RwLock<HashMap<u32, Arc<Vec<u8>>>>,
+    last_auto_purge_ms: AtomicI64,
+    // Per-namespace write callbacks registered by `set_triggers`; see `fire_set_triggers` and
+    // `fire_delete_triggers`.
+    triggers: RwLock<HashMap<CowStr, NamespaceTriggers>>,
+}
+
+/// Python callables to invoke after a write to a namespace commits, borrowing the
+/// put/remove/replace trigger model from cozo's `SetTriggers`. All three are optional and
+/// independent: a `set` that creates a new key fires `on_set`, a `set` that overwrites an
+/// existing key fires `on_replace` instead, and a successful `delete`/`delete_many` fires
+/// `on_delete`. Each callback is invoked once per affected key, as `callback(key)`.
+#[derive(Clone, Default)]
+struct NamespaceTriggers {
+    on_set: Option<Py<PyAny>>,
+    on_delete: Option<Py<PyAny>>,
+    on_replace: Option<Py<PyAny>>,
 }
 
 struct InternalInsertTriple {
     key: CowStr,
     codecs_blob: CodecsBlob,
     value: Vec<u8>,
+    created_at_ms: i64,
+    expires_at_ms: Option<i64>,
+}
+
+/// Version tag for the binary container `export_namespace`/`import_namespace` read and write,
+/// so a future format change can be detected rather than misparsed.
+const NAMESPACE_EXPORT_FORMAT_VERSION: u8 = 1;
+
+/// One row of a namespace export, carrying the raw `codecs`/`value` column bytes exactly as
+/// stored (not decoded through `into_data_codecs_decoded`), so the exact codec chain round-trips.
+struct ExportedRow {
+    key: String,
+    codecs_blob: Vec<u8>,
+    value: Vec<u8>,
+    created_at_ms: i64,
+    expires_at_ms: Option<i64>,
+}
+
+/// Writes `bytes` as a varint length prefix followed by the bytes themselves.
+fn write_len_prefixed(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// Reads a `write_len_prefixed` blob from the start of `data`, returning it and the number of
+/// bytes consumed.
+fn read_len_prefixed(data: &[u8]) -> PyResult<(&[u8], usize)> {
+    let (len, header_len) = read_varint(data)?;
+    let bytes = data
+        .get(header_len..header_len + len as usize)
+        .ok_or_else(|| to_talsi_error("Truncated namespace export"))?;
+    Ok((bytes, header_len + len as usize))
+}
+
+/// Serializes `rows` into the binary container `export_namespace` returns: a format version
+/// byte, a varint row count, then each row as `key`, `codecs`, `value` (each length-prefixed),
+/// `created_at_ms` (8 bytes, little-endian), and `expires_at_ms` (a has-value byte followed by
+/// 8 bytes if present).
+fn encode_namespace_export(rows: &[ExportedRow]) -> Vec<u8> {
+    let mut out = vec![NAMESPACE_EXPORT_FORMAT_VERSION];
+    write_varint(&mut out, rows.len() as u64);
+    for row in rows {
+        write_len_prefixed(&mut out, row.key.as_bytes());
+        write_len_prefixed(&mut out, &row.codecs_blob);
+        write_len_prefixed(&mut out, &row.value);
+        out.extend_from_slice(&row.created_at_ms.to_le_bytes());
+        match row.expires_at_ms {
+            Some(ms) => {
+                out.push(1);
+                out.extend_from_slice(&ms.to_le_bytes());
+            }
+            None => out.push(0),
+        }
+    }
+    out
+}
+
+/// Parses a blob produced by `encode_namespace_export`.
+fn decode_namespace_export(data: &[u8]) -> PyResult<Vec<ExportedRow>> {
+    let (&format_version, rest) = data
+        .split_first()
+        .ok_or_else(|| to_talsi_error("Empty namespace export"))?;
+    if format_version != NAMESPACE_EXPORT_FORMAT_VERSION {
+        return Err(to_talsi_error(format!(
+            "Unsupported namespace export format version: {}",
+            format_version
+        )));
+    }
+    let (row_count, mut pos) = read_varint(rest)?;
+    let mut rows = Vec::with_capacity(row_count as usize);
+    for _ in 0..row_count {
+        let (key, n) = read_len_prefixed(&rest[pos..])?;
+        let key = String::from_utf8(key.to_vec()).map_err(to_talsi_error)?;
+        pos += n;
+        let (codecs_blob, n) = read_len_prefixed(&rest[pos..])?;
+        let codecs_blob = codecs_blob.to_vec();
+        pos += n;
+        let (value, n) = read_len_prefixed(&rest[pos..])?;
+        let value = value.to_vec();
+        pos += n;
+        let created_at_bytes = rest
+            .get(pos..pos + 8)
+            .ok_or_else(|| to_talsi_error("Truncated namespace export"))?;
+        let created_at_ms = i64::from_le_bytes(created_at_bytes.try_into().unwrap());
+        pos += 8;
+        let has_expires = *rest
+            .get(pos)
+            .ok_or_else(|| to_talsi_error("Truncated namespace export"))?;
+        pos += 1;
+        let expires_at_ms = if has_expires != 0 {
+            let bytes = rest
+                .get(pos..pos + 8)
+                .ok_or_else(|| to_talsi_error("Truncated namespace export"))?;
+            pos += 8;
+            Some(i64::from_le_bytes(bytes.try_into().unwrap()))
+        } else {
+            None
+        };
+        rows.push(ExportedRow {
+            key,
+            codecs_blob,
+            value,
+            created_at_ms,
+            expires_at_ms,
+        });
+    }
+    Ok(rows)
 }
 
 struct InternalStoredRecord {
@@ -82,7 +236,20 @@ impl InternalStoredDataAndMnemonic {
 }
 
 impl InternalStoredRecord {
-    fn into_data_codecs_decoded(self) -> PyResult<InternalStoredDataAndMnemonic> {
+    /// If this record's outermost data codec is the dictionary one, returns the dictionary id
+    /// its value is framed with, without doing any actual decompression.
+    fn dict_id(&self) -> PyResult<Option<u32>> {
+        if self.codecs_blob.last() != Some(&crate::zstd_codec::DICT_MNEMONIC) {
+            return Ok(None);
+        }
+        let (dict_id, _, _) = crate::zstd_codec::split_dictionary_frame(&self.value)?;
+        Ok(Some(dict_id))
+    }
+
+    fn into_data_codecs_decoded(
+        self,
+        dicts: &ZstdDictCacheView,
+    ) -> PyResult<InternalStoredDataAndMnemonic> {
         let mut value = self.value;
         let (python_codec_mnemonic, data_codecs) = self
             .codecs_blob
@@ -91,10 +258,22 @@ impl InternalStoredRecord {
         if !data_codecs.is_empty() {
             // Decode data codecs in reverse order
             for mnemonic in data_codecs.iter().rev() {
-                value = decode_from_data_and_mnemonic(DataAndMnemonic {
-                    data: value,
-                    codec: *mnemonic,
-                })?;
+                value = if *mnemonic == crate::zstd_codec::DICT_MNEMONIC {
+                    let (dict_id, original_len, payload) =
+                        crate::zstd_codec::split_dictionary_frame(&value)?;
+                    let dict = dicts.get(dict_id)?;
+                    crate::zstd_codec::decode_with_dictionary(
+                        payload,
+                        dict_id,
+                        original_len,
+                        &dict,
+                    )?
+                } else {
+                    decode_from_data_and_mnemonic(DataAndMnemonic {
+                        data: value,
+                        codec: *mnemonic,
+                    })?
+                };
             }
         }
         Ok(InternalStoredDataAndMnemonic {
@@ -108,9 +287,65 @@ impl InternalStoredRecord {
     }
 }
 
-#[inline]
-fn to_talsi_error<T: ToString>(e: T) -> PyErr {
-    PyErr::new::<TalsiError, _>(e.to_string())
+/// Resolves trained zstd dictionaries by id, consulting `Storage`'s in-memory cache before
+/// falling back to `tl__zstd_dictionaries`. Needs the (non-`Sync`) `Connection`, so it's only
+/// used to populate the cache up front, sequentially, before any parallel decoding starts.
+struct ZstdDictCache<'a> {
+    conn: &'a Connection,
+    cache: &'a RwLock<HashMap<u32, Arc<Vec<u8>>>>,
+}
+
+impl ZstdDictCache<'_> {
+    fn get(&self, dict_id: u32) -> PyResult<Arc<Vec<u8>>> {
+        if let Some(dict) = self.cache.read().unwrap().get(&dict_id) {
+            return Ok(dict.clone());
+        }
+        let bytes: Vec<u8> = self
+            .conn
+            .query_row(
+                "SELECT bytes FROM tl__zstd_dictionaries WHERE id = ?",
+                params![dict_id],
+                |row| row.get(0),
+            )
+            .map_err(to_talsi_error)?;
+        let dict = Arc::new(bytes);
+        self.cache.write().unwrap().insert(dict_id, dict.clone());
+        Ok(dict)
+    }
+
+    /// A cache-only view that doesn't hold the `Connection`, safe to share across the rayon
+    /// threads used to decode a batch of records in parallel.
+    fn view(&self) -> ZstdDictCacheView<'_> {
+        ZstdDictCacheView { cache: self.cache }
+    }
+}
+
+struct ZstdDictCacheView<'a> {
+    cache: &'a RwLock<HashMap<u32, Arc<Vec<u8>>>>,
+}
+
+impl ZstdDictCacheView<'_> {
+    fn get(&self, dict_id: u32) -> PyResult<Arc<Vec<u8>>> {
+        self.cache
+            .read()
+            .unwrap()
+            .get(&dict_id)
+            .cloned()
+            .ok_or_else(|| to_talsi_error(format!("Zstd dictionary {} was not preloaded", dict_id)))
+    }
+}
+
+fn ensure_zstd_dictionaries_table(conn: &Connection) -> PyResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tl__zstd_dictionaries (
+            id INTEGER PRIMARY KEY,
+            bytes BLOB NOT NULL,
+            created_at_ms TIMESTAMP NOT NULL
+        )",
+        [],
+    )
+    .map_err(to_talsi_error)?;
+    Ok(())
 }
 
 fn ensure_namespace_table(conn: &Connection, namespace: &str) -> PyResult<()> {
@@ -178,44 +413,175 @@ impl Storage {
 
     #[inline]
     #[instrument(skip_all)]
-    fn internal_insert(
-        &self,
-        namespace: &str,
-        now: Duration,
-        expires_at: Option<Duration>,
-        iits: &[InternalInsertTriple],
-    ) -> PyResult<usize> {
-        let now_ms = now.as_millis() as i64;
-        let expires_ms = expires_at.map(|t| t.as_millis() as i64);
+    fn internal_insert(&self, namespace: &str, iits: &[InternalInsertTriple]) -> PyResult<usize> {
         let maybe_conn = self.conn.lock().unwrap();
         let conn = maybe_conn
             .as_ref()
             .ok_or_else(|| to_talsi_error("Connection is closed"))?;
         self.ensure_namespace_table(conn, namespace)?;
         let tx = conn.unchecked_transaction().map_err(to_talsi_error)?;
+        // The primary key is `(key, version)`, not `key` alone, so a plain `INSERT OR REPLACE`
+        // only replaces a row at version 0; an unconditional `set` must still win over any row
+        // left at a different version by `set_if_version`, so the old row is deleted first and
+        // the key is reinserted fresh at version 0.
+        let mut delete_stmt = tx
+            .prepare_cached(&format!("DELETE FROM tl_{} WHERE key = ?", namespace))
+            .map_err(to_talsi_error)?;
         let mut stmt = tx
-            .prepare_cached(&format!("INSERT OR REPLACE INTO tl_{} (key, codecs, value, created_at_ms, expires_at_ms) VALUES (?, ?, ?, ?, ?)", namespace))
+            .prepare_cached(&format!("INSERT INTO tl_{} (key, version, codecs, value, created_at_ms, expires_at_ms) VALUES (?, 0, ?, ?, ?, ?)", namespace))
             .map_err(to_talsi_error)?;
+        // `delete_stmt`'s row count tells us whether a key already existed, which is what
+        // distinguishes an `on_set` (brand new key) from an `on_replace` (overwrite) trigger.
+        let mut inserted_keys: Vec<CowStr> = Vec::new();
+        let mut replaced_keys: Vec<CowStr> = Vec::new();
         for iit in iits {
             let InternalInsertTriple {
                 key,
                 codecs_blob,
                 value: data_encoded,
+                created_at_ms,
+                expires_at_ms,
             } = iit;
+            let existed = delete_stmt
+                .execute(params![key.as_ref()])
+                .map_err(to_talsi_error)?
+                > 0;
             stmt.execute(params![
                 key.as_ref(),
-                codecs_blob.as_slice(),
+                encode_codecs_blob(codecs_blob),
                 data_encoded,
-                now_ms,
-                expires_ms
+                created_at_ms,
+                expires_at_ms
             ])
             .map_err(to_talsi_error)?;
+            if existed {
+                replaced_keys.push(key.clone());
+            } else {
+                inserted_keys.push(key.clone());
+            }
         }
         drop(stmt);
+        drop(delete_stmt);
         tx.commit().map_err(to_talsi_error)?;
+        let now_ms = now_ms()?;
+        self.maybe_auto_purge(conn, now_ms)?;
+        // Drop the `conn` lock before firing triggers: they call back into Python, and a
+        // callback that calls back into this `Storage` (e.g. to maintain a derived index) would
+        // deadlock on this same non-reentrant `Mutex` otherwise.
+        drop(maybe_conn);
+        self.fire_set_triggers(namespace, &inserted_keys, &replaced_keys)?;
         Ok(iits.len())
     }
 
+    /// Calls `namespace`'s `on_set`/`on_replace` triggers (registered via `set_triggers`), once
+    /// per key in `inserted_keys`/`replaced_keys` respectively. A no-op if no triggers are
+    /// registered for `namespace`. Called from `internal_insert`, which usually runs inside
+    /// `py.detach`, so it re-acquires the GIL itself via `Python::attach`.
+    fn fire_set_triggers(
+        &self,
+        namespace: &str,
+        inserted_keys: &[CowStr],
+        replaced_keys: &[CowStr],
+    ) -> PyResult<()> {
+        if inserted_keys.is_empty() && replaced_keys.is_empty() {
+            return Ok(());
+        }
+        let (on_set, on_replace) = match self.triggers.read().unwrap().get(namespace) {
+            Some(t) => (t.on_set.clone(), t.on_replace.clone()),
+            None => return Ok(()),
+        };
+        if on_set.is_none() && on_replace.is_none() {
+            return Ok(());
+        }
+        Python::attach(|py| {
+            if let Some(cb) = &on_set {
+                for key in inserted_keys {
+                    cb.call1(py, (key.as_ref(),))?;
+                }
+            }
+            if let Some(cb) = &on_replace {
+                for key in replaced_keys {
+                    cb.call1(py, (key.as_ref(),))?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Calls `namespace`'s `on_delete` trigger (registered via `set_triggers`), once per key in
+    /// `deleted_keys`. A no-op if no trigger is registered for `namespace`. Acquires the GIL
+    /// itself via `Python::attach`, so it works whether or not the caller holds it.
+    fn fire_delete_triggers(&self, namespace: &str, deleted_keys: &[CowStr]) -> PyResult<()> {
+        if deleted_keys.is_empty() {
+            return Ok(());
+        }
+        let on_delete = match self.triggers.read().unwrap().get(namespace) {
+            Some(t) => t.on_delete.clone(),
+            None => return Ok(()),
+        };
+        let Some(on_delete) = on_delete else {
+            return Ok(());
+        };
+        Python::attach(|py| {
+            for key in deleted_keys {
+                on_delete.call1(py, (key.as_ref(),))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Deletes expired rows from `namespace` (or every known namespace when `None`), returning
+    /// the number of rows removed. "Known" namespaces are the ones this `Storage` has written
+    /// to since it was opened; see `known_namespaces`.
+    #[inline]
+    fn purge_expired_in(
+        &self,
+        conn: &Connection,
+        now_ms: i64,
+        namespace: Option<&str>,
+    ) -> PyResult<usize> {
+        let namespaces: Vec<CowStr> = match namespace {
+            Some(ns) => vec![Cow::from(ns.to_owned())],
+            None => self.known_namespaces.read().unwrap().iter().cloned().collect(),
+        };
+        let mut total = 0;
+        for ns in namespaces {
+            let query = format!(
+                "DELETE FROM tl_{} WHERE expires_at_ms IS NOT NULL AND expires_at_ms <= ?",
+                ns
+            );
+            match ignore_no_such_table(conn.prepare(&query)).map_err(to_talsi_error)? {
+                StatementResult::Stmt(mut stmt) => {
+                    total += stmt.execute(params![now_ms]).map_err(to_talsi_error)?;
+                }
+                StatementResult::NoSuchTable => {}
+            }
+        }
+        Ok(total)
+    }
+
+    /// Opportunistically sweeps expired rows once `auto_purge_interval_ms` has elapsed since
+    /// the last sweep. Uses a compare-exchange on `last_auto_purge_ms` so that, under
+    /// concurrent writers, only one of them actually runs the sweep per interval.
+    fn maybe_auto_purge(&self, conn: &Connection, now_ms: i64) -> PyResult<()> {
+        let Some(interval_ms) = self.settings.auto_purge_interval_ms else {
+            return Ok(());
+        };
+        let last = self.last_auto_purge_ms.load(Ordering::Relaxed);
+        if now_ms - last < interval_ms as i64 {
+            return Ok(());
+        }
+        if self
+            .last_auto_purge_ms
+            .compare_exchange(last, now_ms, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return Ok(()); // Another thread just ran the sweep.
+        }
+        self.purge_expired_in(conn, now_ms, None)?;
+        Ok(())
+    }
+
     #[inline]
     #[instrument(skip_all)]
     fn internal_delete(&self, namespace: CowStr, keys: &[CowStr]) -> PyResult<usize> {
@@ -224,11 +590,13 @@ impl Storage {
             .as_ref()
             .ok_or_else(|| to_talsi_error("Connection is closed"))?;
         let tx = conn.unchecked_transaction().map_err(to_talsi_error)?;
-        let mut n = 0;
+        // `RETURNING key` tells us exactly which of `keys` existed and were removed, which
+        // `on_delete` triggers need (as opposed to firing for keys that were never there).
+        let mut deleted_keys: Vec<CowStr> = Vec::new();
         for keys in keys.chunks(self.max_num_binds) {
             let placeholders = keys.iter().map(|_| "?").collect::<Vec<_>>().join(",");
             let query = &format!(
-                "DELETE FROM tl_{} WHERE key IN ({})",
+                "DELETE FROM tl_{} WHERE key IN ({}) RETURNING key",
                 namespace, placeholders
             );
             let mut stmt = match ignore_no_such_table(tx.prepare(query)).map_err(to_talsi_error)? {
@@ -237,10 +605,14 @@ impl Storage {
                     return Ok(0);
                 }
             };
-            let res = stmt.execute(params_from_iter(keys.iter().map(AsRef::as_ref)));
+            let res = stmt.query_map(params_from_iter(keys.iter().map(AsRef::as_ref)), |row| {
+                row.get::<_, String>(0)
+            });
             match res {
                 Ok(rows) => {
-                    n += rows;
+                    for row in rows {
+                        deleted_keys.push(Cow::from(row.map_err(to_talsi_error)?));
+                    }
                 }
                 Err(e) => {
                     if e.to_string().contains("no such table") {
@@ -252,7 +624,42 @@ impl Storage {
             }
         }
         tx.commit().map_err(to_talsi_error)?;
-        Ok(n)
+        // See the matching comment in `internal_insert`: triggers call back into Python and
+        // must not run while `conn`'s lock is still held.
+        drop(maybe_conn);
+        self.fire_delete_triggers(namespace.as_ref(), &deleted_keys)?;
+        Ok(deleted_keys.len())
+    }
+
+    /// Returns whichever of `keys` already have a row in `namespace`, ignoring TTL expiry (this
+    /// is used by `import_namespace` to decide what to skip when `overwrite=False`, which cares
+    /// about row presence rather than read-time visibility). Chunked by `max_num_binds` like
+    /// `has_many`/`get_many`.
+    fn existing_keys_in(
+        &self,
+        conn: &Connection,
+        namespace: &str,
+        keys: &[String],
+    ) -> PyResult<HashSet<String>> {
+        let mut existing = HashSet::new();
+        for chunk in keys.chunks(self.max_num_binds) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            let query = format!("SELECT key FROM tl_{} WHERE key IN ({})", namespace, placeholders);
+            match ignore_no_such_table(conn.prepare(&query)).map_err(to_talsi_error)? {
+                StatementResult::Stmt(mut stmt) => {
+                    let found = stmt
+                        .query_map(params_from_iter(chunk.iter().map(String::as_str)), |row| {
+                            row.get::<_, String>(0)
+                        })
+                        .map_err(to_talsi_error)?
+                        .collect::<Result<Vec<String>, _>>()
+                        .map_err(to_talsi_error)?;
+                    existing.extend(found);
+                }
+                StatementResult::NoSuchTable => {}
+            }
+        }
+        Ok(existing)
     }
 }
 
@@ -266,23 +673,147 @@ PRAGMA temp_store=MEMORY;
 #[pymethods]
 impl Storage {
     #[new]
-    #[pyo3(signature = (path, *, allow_pickle = false))]
-    fn new(path: &str, allow_pickle: bool) -> PyResult<Self> {
+    #[pyo3(signature = (path, *, allow_pickle = false, data_codec = None, data_codec_level = None, data_codec_element_width = None, auto_purge_interval_ms = None))]
+    fn new(
+        path: &str,
+        allow_pickle: bool,
+        data_codec: Option<&str>,
+        data_codec_level: Option<i32>,
+        data_codec_element_width: Option<u8>,
+        auto_purge_interval_ms: Option<u64>,
+    ) -> PyResult<Self> {
         let conn = Connection::open(path).map_err(to_talsi_error)?;
         conn.set_prepared_statement_cache_capacity(64);
         conn.execute_batch(INIT_PRAGMAS).map_err(to_talsi_error)?;
-        let max_num_binds = conn
-            .limit(Limit::SQLITE_LIMIT_VARIABLE_NUMBER)
-            .map_err(to_talsi_error)? as usize;
+        let max_num_binds = conn.limit(Limit::SQLITE_LIMIT_VARIABLE_NUMBER) as usize;
+        let data_codec = match data_codec {
+            Some(name) => DataCodecChoice::parse(name, data_codec_level, data_codec_element_width)
+                .map_err(to_talsi_error)?,
+            None => DataCodecChoice::Default,
+        };
 
         Ok(Storage {
             conn: Mutex::new(Some(conn)),
             max_num_binds,
             known_namespaces: RwLock::new(HashSet::new()),
-            settings: StorageSettings { allow_pickle },
+            settings: StorageSettings {
+                allow_pickle,
+                data_codec: RwLock::new(data_codec),
+                auto_purge_interval_ms,
+            },
+            zstd_dicts: RwLock::new(HashMap::new()),
+            last_auto_purge_ms: AtomicI64::new(0),
+            triggers: RwLock::new(HashMap::new()),
         })
     }
 
+    /// Trains a zstd dictionary from a sample of this namespace's existing values and persists
+    /// it to `tl__zstd_dictionaries`, returning its id. Use the id with `use_zstd_dictionary`.
+    #[pyo3(signature = (namespace, *, sample_size = 100, max_dict_size = 112_640))]
+    fn train_zstd_dictionary(
+        &self,
+        namespace: StringOrByteString,
+        sample_size: usize,
+        max_dict_size: usize,
+    ) -> PyResult<u32> {
+        let namespace = string_or_bytestring_as_string(namespace)?;
+        let maybe_conn = self.conn.lock().unwrap();
+        let conn = maybe_conn
+            .as_ref()
+            .ok_or_else(|| to_talsi_error("Connection is closed"))?;
+        let mut stmt = match ignore_no_such_table(
+            conn.prepare(&format!("SELECT value, codecs FROM tl_{} LIMIT ?", namespace)),
+        )
+        .map_err(to_talsi_error)?
+        {
+            StatementResult::Stmt(stmt) => stmt,
+            StatementResult::NoSuchTable => {
+                return Err(to_talsi_error("Namespace has no stored values to train from"));
+            }
+        };
+        // `value` is only the py-encoding verbatim for rows stored uncompressed; anything that
+        // went through a data codec (snappy/zstd/dict/shuffle/...) is a compressed frame here, not
+        // the bytes `get_best_data_encoding` will actually be asked to compress. Undo each row's
+        // data-codec chain first so the dictionary is trained on the same representation it will
+        // later be applied against, regardless of how large the sampled rows happen to be.
+        let raw_samples: Vec<InternalStoredRecord> = stmt
+            .query_map(params![sample_size as i64], |row| {
+                let codecs_blob = match row.get_ref(1)? {
+                    ValueRef::Blob(v) => decode_codecs_blob(v).map_err(corrupt_codecs_blob)?,
+                    _ => panic!("invalid codec blob type"),
+                };
+                Ok(InternalStoredRecord {
+                    key: None,
+                    value: row.get(0)?,
+                    codecs_blob,
+                    expires_at_ms: None,
+                })
+            })
+            .map_err(to_talsi_error)?
+            .collect::<Result<Vec<InternalStoredRecord>, _>>()
+            .map_err(to_talsi_error)?;
+        if raw_samples.is_empty() {
+            return Err(to_talsi_error("No sample values available to train a dictionary"));
+        }
+        let dicts = ZstdDictCache {
+            conn,
+            cache: &self.zstd_dicts,
+        };
+        for rec in &raw_samples {
+            if let Some(dict_id) = rec.dict_id()? {
+                dicts.get(dict_id)?; // populate the cache before decoding below
+            }
+        }
+        let dicts_view = dicts.view();
+        let samples: Vec<Vec<u8>> = raw_samples
+            .into_iter()
+            .map(|rec| {
+                rec.into_data_codecs_decoded(&dicts_view)
+                    .map(|idd| idd.data_and_mnemonic.data)
+            })
+            .collect::<PyResult<Vec<Vec<u8>>>>()?;
+        let dict = crate::zstd_codec::train_dictionary(&samples, max_dict_size)?;
+
+        ensure_zstd_dictionaries_table(conn)?;
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(to_talsi_error)?
+            .as_millis() as i64;
+        conn.execute(
+            "INSERT INTO tl__zstd_dictionaries (bytes, created_at_ms) VALUES (?, ?)",
+            params![dict.as_slice(), now_ms],
+        )
+        .map_err(to_talsi_error)?;
+        let dict_id = conn.last_insert_rowid() as u32;
+        self.zstd_dicts
+            .write()
+            .unwrap()
+            .insert(dict_id, Arc::new(dict));
+        Ok(dict_id)
+    }
+
+    /// Switches writes over to zstd-with-dictionary compression using a previously trained
+    /// dictionary id. The dictionary must already exist (via `train_zstd_dictionary` or in the
+    /// already-opened database file).
+    #[pyo3(signature = (dict_id, *, level = 3))]
+    fn use_zstd_dictionary(&self, dict_id: u32, level: i32) -> PyResult<()> {
+        let maybe_conn = self.conn.lock().unwrap();
+        let conn = maybe_conn
+            .as_ref()
+            .ok_or_else(|| to_talsi_error("Connection is closed"))?;
+        let dict = (ZstdDictCache {
+            conn,
+            cache: &self.zstd_dicts,
+        })
+        .get(dict_id)?;
+        *self.settings.data_codec.write().unwrap() = DataCodecChoice::ZstdDict {
+            level,
+            dict_id,
+            dict,
+        };
+        Ok(())
+    }
+
     fn close(&self) -> PyResult<()> {
         let mut conn = self.conn.lock().unwrap();
         let conn = conn.take();
@@ -303,31 +834,28 @@ impl Storage {
         ttl_ms: Option<u64>,
     ) -> PyResult<()> {
         let py_enc_result = get_best_py_encoding(py, value.bind(py), self.settings.allow_pickle)?;
-        py.allow_threads(|| {
+        py.detach(|| {
             let key = string_or_bytestring_as_string(key)?;
             let namespace = string_or_bytestring_as_string(namespace)?;
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map_err(to_talsi_error)?;
-            let expires_at = ttl_ms.map(|ttl| now + Duration::from_millis(ttl));
-            let data_enc_result = get_best_data_encoding(&py_enc_result.data)?;
+            let now_ms = now_ms()?;
+            let expires_at_ms = ttl_ms.map(|ttl| now_ms + ttl as i64);
+            let data_codec = self.settings.data_codec.read().unwrap().clone();
+            let data_enc_result = get_best_data_encoding(&py_enc_result.data, data_codec)?;
             let DataAndMnemonics {
                 data: data_encoded,
                 codecs: codecs_blob,
             } = match data_enc_result {
-                Some(data_enc_result) => DataAndMnemonics::from_two(
-                    data_enc_result.data,
-                    py_enc_result.codec,
-                    data_enc_result.codec,
-                ),
+                Some(chain) => chain.prepend(py_enc_result.codec),
                 None => DataAndMnemonics::from_single(py_enc_result), // didn't encode further
             };
             let iit = InternalInsertTriple {
                 key,
                 codecs_blob,
                 value: data_encoded,
+                created_at_ms: now_ms,
+                expires_at_ms,
             };
-            self.internal_insert(namespace.as_ref(), now, expires_at, &[iit])?;
+            self.internal_insert(namespace.as_ref(), &[iit])?;
             Ok(())
         })
     }
@@ -339,7 +867,7 @@ impl Storage {
         namespace: StringOrByteString,
         key: StringOrByteString,
     ) -> PyResult<Option<Py<PyAny>>> {
-        let idd = py.allow_threads(|| -> PyResult<Option<InternalStoredDataAndMnemonic>> {
+        let idd = py.detach(|| -> PyResult<Option<InternalStoredDataAndMnemonic>> {
             let key = string_or_bytestring_as_string(key)?;
             let namespace = string_or_bytestring_as_string(namespace)?;
             let maybe_conn = self.conn.lock().unwrap();
@@ -347,7 +875,7 @@ impl Storage {
                 .as_ref()
                 .ok_or(to_talsi_error("Connection is closed"))?;
             let mut stmt = match ignore_no_such_table(conn.prepare_cached(&format!(
-                "SELECT value, codecs, expires_at_ms FROM tl_{} WHERE key = ? LIMIT 1",
+                "SELECT value, codecs, expires_at_ms FROM tl_{} WHERE key = ? AND (expires_at_ms IS NULL OR expires_at_ms > ?) LIMIT 1",
                 namespace
             )))
             .map_err(to_talsi_error)?
@@ -357,10 +885,13 @@ impl Storage {
                     return Ok(None);
                 }
             };
+            let now_ms = now_ms()?;
             let isr: Option<InternalStoredRecord> = stmt
-                .query_row(params![key.as_ref()], |row| {
+                .query_row(params![key.as_ref(), now_ms], |row| {
                     let codecs_blob = match row.get_ref(1)? {
-                        ValueRef::Blob(v) => CodecsBlob::from_slice(v),
+                        ValueRef::Blob(v) => {
+                            decode_codecs_blob(v).map_err(corrupt_codecs_blob)?
+                        }
                         _ => panic!("invalid codec blob type"),
                     };
                     Ok(InternalStoredRecord {
@@ -372,14 +903,22 @@ impl Storage {
                 })
                 .optional()
                 .map_err(to_talsi_error)?;
+            let dicts = ZstdDictCache {
+                conn,
+                cache: &self.zstd_dicts,
+            };
             match isr {
-                Some(isr) => Ok(Some(isr.into_data_codecs_decoded()?)),
+                Some(isr) => {
+                    if let Some(dict_id) = isr.dict_id()? {
+                        dicts.get(dict_id)?; // populate the cache before decoding
+                    }
+                    Ok(Some(isr.into_data_codecs_decoded(&dicts.view())?))
+                }
                 None => Ok(None),
             }
         })?;
         match idd {
             Some(idd) => {
-                // TODO: check expiry
                 let (_, py_val) = idd.into_python(py, &self.settings)?;
                 Ok(Some(py_val.into()))
             }
@@ -387,6 +926,204 @@ impl Storage {
         }
     }
 
+    /// Like `get`, but coerces the result to `kind` (one of `"bytes"`, `"int"`, `"float"`,
+    /// `"bool"`, `"str"`, or `"timestamp"`) rather than returning whatever type the stored codec
+    /// chain round-trips to. Useful when `namespace` holds values written by another
+    /// process/language and the caller wants a guaranteed concrete type. Raises `TalsiError` if
+    /// the value can't be coerced.
+    #[pyo3(signature = (namespace, key, kind))]
+    fn get_as(
+        &self,
+        py: Python<'_>,
+        namespace: StringOrByteString,
+        key: StringOrByteString,
+        kind: &str,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        let kind = ValueKind::from_str(kind)?;
+        match self.get(py, namespace, key)? {
+            Some(value) => Ok(Some(coerce_value(py, value.into_bound(py), kind)?.unbind())),
+            None => Ok(None),
+        }
+    }
+
+    /// Like `get`, but also returns the row's current `version`, for use with
+    /// `set_if_version` to perform an optimistic compare-and-swap update.
+    #[pyo3(signature = (namespace, key))]
+    fn get_versioned(
+        &self,
+        py: Python<'_>,
+        namespace: StringOrByteString,
+        key: StringOrByteString,
+    ) -> PyResult<Option<(Py<PyAny>, i64)>> {
+        let idd_and_version =
+            py.detach(|| -> PyResult<Option<(InternalStoredDataAndMnemonic, i64)>> {
+                let key = string_or_bytestring_as_string(key)?;
+                let namespace = string_or_bytestring_as_string(namespace)?;
+                let maybe_conn = self.conn.lock().unwrap();
+                let conn = maybe_conn
+                    .as_ref()
+                    .ok_or(to_talsi_error("Connection is closed"))?;
+                let mut stmt = match ignore_no_such_table(conn.prepare_cached(&format!(
+                    "SELECT value, codecs, expires_at_ms, version FROM tl_{} WHERE key = ? AND (expires_at_ms IS NULL OR expires_at_ms > ?) LIMIT 1",
+                    namespace
+                )))
+                .map_err(to_talsi_error)?
+                {
+                    StatementResult::Stmt(stmt) => stmt,
+                    StatementResult::NoSuchTable => {
+                        return Ok(None);
+                    }
+                };
+                let now_ms = now_ms()?;
+                let isr_and_version: Option<(InternalStoredRecord, i64)> = stmt
+                    .query_row(params![key.as_ref(), now_ms], |row| {
+                        let codecs_blob = match row.get_ref(1)? {
+                            ValueRef::Blob(v) => {
+                                decode_codecs_blob(v).map_err(corrupt_codecs_blob)?
+                            }
+                            _ => panic!("invalid codec blob type"),
+                        };
+                        let version: i64 = row.get(3)?;
+                        Ok((
+                            InternalStoredRecord {
+                                key: None,
+                                value: row.get(0)?,
+                                codecs_blob,
+                                expires_at_ms: row.get(2)?,
+                            },
+                            version,
+                        ))
+                    })
+                    .optional()
+                    .map_err(to_talsi_error)?;
+                let dicts = ZstdDictCache {
+                    conn,
+                    cache: &self.zstd_dicts,
+                };
+                match isr_and_version {
+                    Some((isr, version)) => {
+                        if let Some(dict_id) = isr.dict_id()? {
+                            dicts.get(dict_id)?; // populate the cache before decoding
+                        }
+                        Ok(Some((isr.into_data_codecs_decoded(&dicts.view())?, version)))
+                    }
+                    None => Ok(None),
+                }
+            })?;
+        match idd_and_version {
+            Some((idd, version)) => {
+                let (_, py_val) = idd.into_python(py, &self.settings)?;
+                Ok(Some((py_val.into(), version)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Writes `value` only if `key`'s current version matches `expected_version`, returning
+    /// whether the write happened. `expected_version = None` means "only write if `key`
+    /// doesn't currently exist" (e.g. for first-write-wins creation). On success the stored
+    /// version is incremented by one (starting at 0 for a newly-created key); call
+    /// `get_versioned` to learn the version to pass next time.
+    #[pyo3(signature = (namespace, key, value, expected_version, *, ttl_ms=None))]
+    fn set_if_version(
+        &self,
+        py: Python<'_>,
+        namespace: StringOrByteString,
+        key: StringOrByteString,
+        value: Py<PyAny>,
+        expected_version: Option<i64>,
+        ttl_ms: Option<u64>,
+    ) -> PyResult<bool> {
+        let py_enc_result = get_best_py_encoding(py, value.bind(py), self.settings.allow_pickle)?;
+        py.detach(|| {
+            let key = string_or_bytestring_as_string(key)?;
+            let namespace = string_or_bytestring_as_string(namespace)?;
+            let now_ms = now_ms()?;
+            let expires_ms = ttl_ms.map(|ttl| now_ms + ttl as i64);
+            let data_codec = self.settings.data_codec.read().unwrap().clone();
+            let data_enc_result = get_best_data_encoding(&py_enc_result.data, data_codec)?;
+            let DataAndMnemonics {
+                data: data_encoded,
+                codecs: codecs_blob,
+            } = match data_enc_result {
+                Some(chain) => chain.prepend(py_enc_result.codec),
+                None => DataAndMnemonics::from_single(py_enc_result),
+            };
+            let maybe_conn = self.conn.lock().unwrap();
+            let conn = maybe_conn
+                .as_ref()
+                .ok_or_else(|| to_talsi_error("Connection is closed"))?;
+            self.ensure_namespace_table(conn, namespace.as_ref())?;
+            let tx = conn.unchecked_transaction().map_err(to_talsi_error)?;
+            let current_version: Option<i64> = tx
+                .query_row(
+                    &format!(
+                        "SELECT version FROM tl_{} WHERE key = ? AND (expires_at_ms IS NULL OR expires_at_ms > ?)",
+                        namespace
+                    ),
+                    params![key.as_ref(), now_ms],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(to_talsi_error)?;
+            let applied = match (current_version, expected_version) {
+                (None, None) => {
+                    // An expired row is invisible to reads (and to `get_versioned`) but still
+                    // occupies the key, so clear it before inserting.
+                    tx.execute(
+                        &format!("DELETE FROM tl_{} WHERE key = ?", namespace),
+                        params![key.as_ref()],
+                    )
+                    .map_err(to_talsi_error)?;
+                    tx.execute(
+                        &format!(
+                            "INSERT INTO tl_{} (key, version, codecs, value, created_at_ms, expires_at_ms) VALUES (?, 0, ?, ?, ?, ?)",
+                            namespace
+                        ),
+                        params![
+                            key.as_ref(),
+                            encode_codecs_blob(&codecs_blob),
+                            data_encoded,
+                            now_ms,
+                            expires_ms
+                        ],
+                    )
+                    .map_err(to_talsi_error)?;
+                    true
+                }
+                (Some(current), Some(expected)) if current == expected => {
+                    // `current_version` was read moments ago in this same DEFERRED transaction,
+                    // but SQLite doesn't take the write lock until this `UPDATE` runs — another
+                    // connection can still sneak in and bump `version` first. Guarding the
+                    // `WHERE` clause on `version = ?` makes that race safe (it'll affect 0 rows
+                    // instead of clobbering the concurrent write), but we must actually check the
+                    // affected-row count here rather than assuming the `WHERE` matched, or we'd
+                    // report success for an update that silently did nothing.
+                    let rows_changed = tx
+                        .execute(
+                            &format!(
+                                "UPDATE tl_{} SET version = version + 1, codecs = ?, value = ?, created_at_ms = ?, expires_at_ms = ? WHERE key = ? AND version = ?",
+                                namespace
+                            ),
+                            params![
+                                encode_codecs_blob(&codecs_blob),
+                                data_encoded,
+                                now_ms,
+                                expires_ms,
+                                key.as_ref(),
+                                current
+                            ],
+                        )
+                        .map_err(to_talsi_error)?;
+                    rows_changed > 0
+                }
+                _ => false, // key exists but caller expected it not to, or the versions don't match
+            };
+            tx.commit().map_err(to_talsi_error)?;
+            Ok(applied)
+        })
+    }
+
     #[pyo3(signature = (namespace, key))]
     fn has(
         &self,
@@ -396,13 +1133,13 @@ impl Storage {
     ) -> PyResult<bool> {
         let key = string_or_bytestring_as_string(key)?;
         let namespace = string_or_bytestring_as_string(namespace)?;
-        py.allow_threads(|| {
+        py.detach(|| {
             let maybe_conn = self.conn.lock().unwrap();
             let conn = maybe_conn
                 .as_ref()
                 .ok_or_else(|| to_talsi_error("Connection is closed"))?;
             let mut stmt = match ignore_no_such_table(conn.prepare_cached(&format!(
-                "SELECT EXISTS(SELECT 1 FROM tl_{} WHERE key = ? LIMIT 1)",
+                "SELECT EXISTS(SELECT 1 FROM tl_{} WHERE key = ? AND (expires_at_ms IS NULL OR expires_at_ms > ?) LIMIT 1)",
                 namespace
             )))
             .map_err(to_talsi_error)?
@@ -412,8 +1149,9 @@ impl Storage {
                     return Ok(false);
                 }
             };
+            let now_ms = now_ms()?;
             let exists: i64 = stmt
-                .query_row(params![key.as_ref()], |row| row.get(0))
+                .query_row(params![key.as_ref(), now_ms], |row| row.get(0))
                 .optional()
                 .map_err(to_talsi_error)?
                 .unwrap_or(0);
@@ -430,16 +1168,17 @@ impl Storage {
     ) -> PyResult<Py<PyFrozenSet>> {
         let keys = strings_or_bytestrings_as_strings(keys)?;
         let namespace = string_or_bytestring_as_string(namespace)?;
-        let extant_keys = py.allow_threads(|| {
+        let extant_keys = py.detach(|| {
             let maybe_conn = self.conn.lock().unwrap();
             let conn = maybe_conn
                 .as_ref()
                 .ok_or_else(|| to_talsi_error("Connection is closed"))?;
             let mut extant_keys: HashSet<String> = HashSet::with_capacity(keys.len());
-            for keys in keys.chunks(self.max_num_binds) {
+            let now_ms = now_ms()?;
+            for keys in keys.chunks(self.max_num_binds.saturating_sub(1).max(1)) {
                 let placeholders = keys.iter().map(|_| "?").collect::<Vec<_>>().join(",");
                 let query = format!(
-                    "SELECT key FROM tl_{} WHERE key IN ({})",
+                    "SELECT key FROM tl_{} WHERE key IN ({}) AND (expires_at_ms IS NULL OR expires_at_ms > ?)",
                     namespace, placeholders
                 );
                 let mut stmt =
@@ -449,10 +1188,11 @@ impl Storage {
                             return Ok::<HashSet<String>, PyErr>(extant_keys);
                         }
                     };
+                let mut bind_params: Vec<&dyn rusqlite::ToSql> =
+                    keys.iter().map(|k| k as &dyn rusqlite::ToSql).collect();
+                bind_params.push(&now_ms);
                 let keys = stmt
-                    .query_map(params_from_iter(keys.iter().map(AsRef::as_ref)), |row| {
-                        row.get(0)
-                    })
+                    .query_map(bind_params.as_slice(), |row| row.get(0))
                     .map_err(to_talsi_error)?
                     .collect::<Result<Vec<String>, _>>()
                     .map_err(to_talsi_error)?;
@@ -491,10 +1231,8 @@ impl Storage {
         ttl_ms: Option<u64>,
     ) -> PyResult<usize> {
         let namespace = string_or_bytestring_as_string(namespace)?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(to_talsi_error)?;
-        let expires_at = ttl_ms.map(|ttl| now + Duration::from_millis(ttl));
+        let now_ms = now_ms()?;
+        let expires_at_ms = ttl_ms.map(|ttl| now_ms + ttl as i64);
         let mut keys: Vec<CowStr> = Vec::new();
         let mut python_values: Vec<DataAndMnemonic> = Vec::new();
         for (key, value) in values.bind(py).iter() {
@@ -506,7 +1244,8 @@ impl Storage {
                 self.settings.allow_pickle,
             )?);
         }
-        py.allow_threads(move || {
+        let data_codec = self.settings.data_codec.read().unwrap().clone();
+        py.detach(move || {
             let mut dat_vec: Vec<DataAndMnemonics> = Vec::with_capacity(python_values.len());
             python_values
                 .into_par_iter()
@@ -515,12 +1254,10 @@ impl Storage {
                          data: py_enc_data,
                          codec: py_enc_mnemonic,
                      }| {
-                        let data_enc_result = get_best_data_encoding(&py_enc_data).unwrap();
+                        let data_enc_result =
+                            get_best_data_encoding(&py_enc_data, data_codec.clone()).unwrap();
                         match data_enc_result {
-                            Some(DataAndMnemonic {
-                                data,
-                                codec: mnemonic,
-                            }) => DataAndMnemonics::from_two(data, py_enc_mnemonic, mnemonic),
+                            Some(chain) => chain.prepend(py_enc_mnemonic),
                             None => DataAndMnemonics::from_data(py_enc_data, py_enc_mnemonic), // Didn't encode further
                         }
                     },
@@ -539,9 +1276,11 @@ impl Storage {
                     key,
                     codecs_blob,
                     value,
+                    created_at_ms: now_ms,
+                    expires_at_ms,
                 });
             }
-            self.internal_insert(namespace.as_ref(), now, expires_at, &iits)
+            self.internal_insert(namespace.as_ref(), &iits)
         })
     }
 
@@ -551,19 +1290,20 @@ impl Storage {
         py: Python<'_>,
         namespace: StringOrByteString,
         keys: Vec<StringOrByteString>,
-    ) -> PyResult<PyObject> {
+    ) -> PyResult<Py<PyAny>> {
         let keys = strings_or_bytestrings_as_strings(keys)?;
         let namespace = string_or_bytestring_as_string(namespace)?;
-        let isrs = py.allow_threads(|| {
+        let isrs = py.detach(|| {
             let maybe_conn = self.conn.lock().unwrap();
             let conn = maybe_conn
                 .as_ref()
                 .ok_or_else(|| to_talsi_error("Connection is closed"))?;
             let mut recs: Vec<InternalStoredRecord> = Vec::new();
-            for keys in keys.chunks(self.max_num_binds) {
+            let now_ms = now_ms()?;
+            for keys in keys.chunks(self.max_num_binds.saturating_sub(1).max(1)) {
                 let placeholders = keys.iter().map(|_| "?").collect::<Vec<_>>().join(",");
                 let query = format!(
-                    "SELECT key, value, codecs, expires_at_ms FROM tl_{} WHERE key IN ({})",
+                    "SELECT key, value, codecs, expires_at_ms FROM tl_{} WHERE key IN ({}) AND (expires_at_ms IS NULL OR expires_at_ms > ?)",
                     namespace, placeholders
                 );
                 let mut stmt =
@@ -573,12 +1313,17 @@ impl Storage {
                             break;
                         }
                     };
+                let mut bind_params: Vec<&dyn rusqlite::ToSql> =
+                    keys.iter().map(|k| k as &dyn rusqlite::ToSql).collect();
+                bind_params.push(&now_ms);
                 let chunk_recs = stmt
                     .query_map(
-                        rusqlite::params_from_iter(keys.iter().map(AsRef::as_ref)),
+                        bind_params.as_slice(),
                         |row| {
                             let codecs_blob = match row.get_ref(2)? {
-                                ValueRef::Blob(v) => CodecsBlob::from_slice(v),
+                                ValueRef::Blob(v) => {
+                                    decode_codecs_blob(v).map_err(corrupt_codecs_blob)?
+                                }
                                 _ => panic!("invalid codec blob type"),
                             };
                             let key: String = row.get(0)?;
@@ -595,20 +1340,142 @@ impl Storage {
                     .map_err(to_talsi_error)?;
                 recs.extend(chunk_recs);
             }
+            let dicts = ZstdDictCache {
+                conn,
+                cache: &self.zstd_dicts,
+            };
+            for rec in &recs {
+                if let Some(dict_id) = rec.dict_id()? {
+                    dicts.get(dict_id)?; // populate the cache before decoding in parallel
+                }
+            }
+            let dicts_view = dicts.view();
             recs.into_par_iter()
-                .map(|isr| isr.into_data_codecs_decoded())
+                .map(|isr| isr.into_data_codecs_decoded(&dicts_view))
                 .collect::<PyResult<Vec<InternalStoredDataAndMnemonic>>>()
                 .map_err(to_talsi_error)
         })?;
         let dict = PyDict::new(py);
         for isr in isrs {
-            // TODO: check expiries
             let (key, py_val) = isr.into_python(py, &self.settings)?;
             dict.set_item(key.unwrap().as_ref(), py_val)?;
         }
         Ok(dict.into())
     }
 
+    /// Like `get_many`, but coerces every value to `kind`; see `get_as`.
+    #[pyo3(signature = (namespace, keys, kind))]
+    fn get_many_as(
+        &self,
+        py: Python<'_>,
+        namespace: StringOrByteString,
+        keys: Vec<StringOrByteString>,
+        kind: &str,
+    ) -> PyResult<Py<PyAny>> {
+        let kind = ValueKind::from_str(kind)?;
+        let values = self.get_many(py, namespace, keys)?;
+        let dict = values.bind(py).downcast::<PyDict>()?;
+        let coerced = PyDict::new(py);
+        for (key, value) in dict.iter() {
+            coerced.set_item(key, coerce_value(py, value, kind)?)?;
+        }
+        Ok(coerced.into())
+    }
+
+    /// Returns one page of up to `batch_size` `(key, value)` pairs from `namespace`, ordered by
+    /// key, along with a continuation cursor to pass back as `after_key` to fetch the next page.
+    /// The cursor is `None` once a page comes back empty, meaning there's nothing left to scan.
+    /// Keyset pagination (`WHERE key > ?  ORDER BY key LIMIT ?`) against the `tl_<ns>_key` index
+    /// keeps each call's memory bounded, unlike `get_many`/`list_keys`, which materialize
+    /// everything at once; `like` and TTL filtering are honored the same way `list_keys` does.
+    #[pyo3(signature = (namespace, *, like=None, batch_size=1000, after_key=None))]
+    fn iter_items(
+        &self,
+        py: Python<'_>,
+        namespace: StringOrByteString,
+        like: Option<StringOrByteString>,
+        batch_size: usize,
+        after_key: Option<StringOrByteString>,
+    ) -> PyResult<(Py<PyAny>, Option<String>)> {
+        let namespace = string_or_bytestring_as_string(namespace)?;
+        let like = like.map(string_or_bytestring_as_string).transpose()?;
+        let after_key = after_key.map(string_or_bytestring_as_string).transpose()?;
+        let isrs = py.detach(|| {
+            let maybe_conn = self.conn.lock().unwrap();
+            let conn = maybe_conn
+                .as_ref()
+                .ok_or_else(|| to_talsi_error("Connection is closed"))?;
+            let now_ms = now_ms()?;
+            let query = match &like {
+                Some(_like) => format!(
+                    "SELECT key, value, codecs, expires_at_ms FROM tl_{} WHERE key > ? AND key LIKE ? AND (expires_at_ms IS NULL OR expires_at_ms > ?) ORDER BY key LIMIT ?",
+                    namespace
+                ),
+                None => format!(
+                    "SELECT key, value, codecs, expires_at_ms FROM tl_{} WHERE key > ? AND (expires_at_ms IS NULL OR expires_at_ms > ?) ORDER BY key LIMIT ?",
+                    namespace
+                ),
+            };
+            let mut stmt =
+                match ignore_no_such_table(conn.prepare(&query)).map_err(to_talsi_error)? {
+                    StatementResult::Stmt(stmt) => stmt,
+                    StatementResult::NoSuchTable => {
+                        return Ok::<Vec<InternalStoredDataAndMnemonic>, PyErr>(Vec::new());
+                    }
+                };
+            let after_key = after_key.as_deref().unwrap_or("");
+            let batch_size = batch_size as i64;
+            let row_mapper = |row: &rusqlite::Row<'_>| {
+                let codecs_blob = match row.get_ref(2)? {
+                    ValueRef::Blob(v) => decode_codecs_blob(v).map_err(corrupt_codecs_blob)?,
+                    _ => panic!("invalid codec blob type"),
+                };
+                let key: String = row.get(0)?;
+                Ok(InternalStoredRecord {
+                    key: Some(Cow::from(key)),
+                    value: row.get(1)?,
+                    codecs_blob,
+                    expires_at_ms: row.get(3)?,
+                })
+            };
+            let recs = match &like {
+                Some(like) => stmt
+                    .query_map(params![after_key, like.as_ref(), now_ms, batch_size], row_mapper)
+                    .map_err(to_talsi_error)?
+                    .collect::<Result<Vec<InternalStoredRecord>, _>>()
+                    .map_err(to_talsi_error)?,
+                None => stmt
+                    .query_map(params![after_key, now_ms, batch_size], row_mapper)
+                    .map_err(to_talsi_error)?
+                    .collect::<Result<Vec<InternalStoredRecord>, _>>()
+                    .map_err(to_talsi_error)?,
+            };
+            let dicts = ZstdDictCache {
+                conn,
+                cache: &self.zstd_dicts,
+            };
+            for rec in &recs {
+                if let Some(dict_id) = rec.dict_id()? {
+                    dicts.get(dict_id)?; // populate the cache before decoding in parallel
+                }
+            }
+            let dicts_view = dicts.view();
+            recs.into_par_iter()
+                .map(|isr| isr.into_data_codecs_decoded(&dicts_view))
+                .collect::<PyResult<Vec<InternalStoredDataAndMnemonic>>>()
+                .map_err(to_talsi_error)
+        })?;
+        let next_cursor = isrs
+            .last()
+            .map(|isr| isr.key.as_ref().unwrap().to_string());
+        let items = PyList::empty(py);
+        for isr in isrs {
+            let (key, py_val) = isr.into_python(py, &self.settings)?;
+            items.append((key.unwrap().as_ref(), py_val))?;
+        }
+        Ok((items.into(), next_cursor))
+    }
+
     #[pyo3(signature = (namespace, *, like=None))]
     fn list_keys(
         &self,
@@ -618,14 +1485,21 @@ impl Storage {
     ) -> PyResult<Vec<String>> {
         let namespace = string_or_bytestring_as_string(namespace)?;
         let like = like.map(string_or_bytestring_as_string).transpose()?;
-        py.allow_threads(|| {
+        py.detach(|| {
             let maybe_conn = self.conn.lock().unwrap();
             let conn = maybe_conn
                 .as_ref()
                 .ok_or_else(|| to_talsi_error("Connection is closed"))?;
+            let now_ms = now_ms()?;
             let query = match &like {
-                Some(_like) => format!("SELECT key FROM tl_{} WHERE key LIKE ?", namespace),
-                None => format!("SELECT key FROM tl_{}", namespace),
+                Some(_like) => format!(
+                    "SELECT key FROM tl_{} WHERE key LIKE ? AND (expires_at_ms IS NULL OR expires_at_ms > ?)",
+                    namespace
+                ),
+                None => format!(
+                    "SELECT key FROM tl_{} WHERE expires_at_ms IS NULL OR expires_at_ms > ?",
+                    namespace
+                ),
             };
             let mut stmt =
                 match ignore_no_such_table(conn.prepare(&query)).map_err(to_talsi_error)? {
@@ -636,12 +1510,12 @@ impl Storage {
                 };
             let keys = match like {
                 Some(like) => stmt
-                    .query_map(params![like.as_ref()], |row| row.get(0))
+                    .query_map(params![like.as_ref(), now_ms], |row| row.get(0))
                     .map_err(to_talsi_error)?
                     .collect::<Result<Vec<String>, _>>()
                     .map_err(to_talsi_error)?,
                 None => stmt
-                    .query_map([], |row| row.get(0))
+                    .query_map(params![now_ms], |row| row.get(0))
                     .map_err(to_talsi_error)?
                     .collect::<Result<Vec<String>, _>>()
                     .map_err(to_talsi_error)?,
@@ -649,4 +1523,143 @@ impl Storage {
             Ok::<Vec<String>, PyErr>(keys)
         })
     }
+
+    /// Deletes expired rows from `namespace`, or from every namespace this `Storage` has
+    /// written to since it was opened if `namespace` is omitted. Returns the number of rows
+    /// removed. Also runs automatically on writes when `auto_purge_interval_ms` is set.
+    #[pyo3(signature = (namespace = None))]
+    fn purge_expired(
+        &self,
+        py: Python<'_>,
+        namespace: Option<StringOrByteString>,
+    ) -> PyResult<usize> {
+        let namespace = namespace.map(string_or_bytestring_as_string).transpose()?;
+        py.detach(|| {
+            let maybe_conn = self.conn.lock().unwrap();
+            let conn = maybe_conn
+                .as_ref()
+                .ok_or_else(|| to_talsi_error("Connection is closed"))?;
+            let now_ms = now_ms()?;
+            self.purge_expired_in(conn, now_ms, namespace.as_deref())
+        })
+    }
+
+    /// Registers Python callbacks to run after writes to `namespace` commit: `on_set` for a
+    /// `set`/`set_many` that creates a new key, `on_replace` for one that overwrites an existing
+    /// key, and `on_delete` for a `delete`/`delete_many` that actually removed a key. Each is
+    /// called as `callback(key)`. Passing `None` for a callback clears it; calling this again
+    /// for a namespace replaces its whole trigger set rather than merging with the old one.
+    #[pyo3(signature = (namespace, *, on_set=None, on_delete=None, on_replace=None))]
+    fn set_triggers(
+        &self,
+        namespace: StringOrByteString,
+        on_set: Option<Py<PyAny>>,
+        on_delete: Option<Py<PyAny>>,
+        on_replace: Option<Py<PyAny>>,
+    ) -> PyResult<()> {
+        let namespace = string_or_bytestring_as_string(namespace)?;
+        self.triggers.write().unwrap().insert(
+            namespace,
+            NamespaceTriggers {
+                on_set,
+                on_delete,
+                on_replace,
+            },
+        );
+        Ok(())
+    }
+
+    /// Dumps every row of `namespace` into a self-describing binary container, preserving the
+    /// raw `codecs` and `value` blobs exactly as stored, along with each row's key and
+    /// timestamps (including already-expired rows). The row `version` is not exported, so
+    /// imported rows start at version 0. Pass the result to
+    /// `import_namespace` to restore it, even into a `Storage` with a different `allow_pickle`
+    /// setting, since the codec mnemonics travel with the data rather than relying on Python
+    /// object identity.
+    fn export_namespace(
+        &self,
+        py: Python<'_>,
+        namespace: StringOrByteString,
+    ) -> PyResult<Py<PyBytes>> {
+        let namespace = string_or_bytestring_as_string(namespace)?;
+        let blob = py.detach(|| {
+            let maybe_conn = self.conn.lock().unwrap();
+            let conn = maybe_conn
+                .as_ref()
+                .ok_or_else(|| to_talsi_error("Connection is closed"))?;
+            let mut stmt = match ignore_no_such_table(conn.prepare(&format!(
+                "SELECT key, codecs, value, created_at_ms, expires_at_ms FROM tl_{}",
+                namespace
+            )))
+            .map_err(to_talsi_error)?
+            {
+                StatementResult::Stmt(stmt) => stmt,
+                StatementResult::NoSuchTable => {
+                    return Ok::<Vec<u8>, PyErr>(encode_namespace_export(&[]));
+                }
+            };
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(ExportedRow {
+                        key: row.get(0)?,
+                        codecs_blob: row.get(1)?,
+                        value: row.get(2)?,
+                        created_at_ms: row.get(3)?,
+                        expires_at_ms: row.get(4)?,
+                    })
+                })
+                .map_err(to_talsi_error)?
+                .collect::<Result<Vec<ExportedRow>, _>>()
+                .map_err(to_talsi_error)?;
+            Ok::<Vec<u8>, PyErr>(encode_namespace_export(&rows))
+        })?;
+        Ok(PyBytes::new(py, &blob).unbind())
+    }
+
+    /// Restores rows from a blob produced by `export_namespace` into `namespace`, replaying them
+    /// through the normal insert path (so triggers fire and auto-purge bookkeeping still
+    /// applies). When `overwrite` is `False` (the default), keys that already have a row in
+    /// `namespace` are left untouched rather than replaced. Returns the number of rows actually
+    /// written.
+    #[pyo3(signature = (namespace, blob, *, overwrite = false))]
+    fn import_namespace(
+        &self,
+        py: Python<'_>,
+        namespace: StringOrByteString,
+        blob: &[u8],
+        overwrite: bool,
+    ) -> PyResult<usize> {
+        let namespace = string_or_bytestring_as_string(namespace)?;
+        let rows = decode_namespace_export(blob)?;
+        py.detach(|| {
+            let skip_keys = if overwrite {
+                HashSet::new()
+            } else {
+                let maybe_conn = self.conn.lock().unwrap();
+                let conn = maybe_conn
+                    .as_ref()
+                    .ok_or_else(|| to_talsi_error("Connection is closed"))?;
+                self.ensure_namespace_table(conn, namespace.as_ref())?;
+                let keys: Vec<String> = rows.iter().map(|row| row.key.clone()).collect();
+                self.existing_keys_in(conn, namespace.as_ref(), &keys)?
+            };
+            let iits = rows
+                .into_iter()
+                .filter(|row| !skip_keys.contains(&row.key))
+                .map(|row| {
+                    Ok(InternalInsertTriple {
+                        key: Cow::from(row.key),
+                        codecs_blob: decode_codecs_blob(&row.codecs_blob)?,
+                        value: row.value,
+                        created_at_ms: row.created_at_ms,
+                        expires_at_ms: row.expires_at_ms,
+                    })
+                })
+                .collect::<PyResult<Vec<InternalInsertTriple>>>()?;
+            if iits.is_empty() {
+                return Ok(0);
+            }
+            self.internal_insert(namespace.as_ref(), &iits)
+        })
+    }
 }
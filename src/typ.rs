@@ -1,4 +1,6 @@
 use either::Either;
+use pyo3::exceptions::PyValueError;
+use pyo3::{PyErr, PyResult};
 use smallvec::{smallvec, SmallVec};
 
 pub(crate) type StringOrByteString = Either<String, Vec<u8>>;
@@ -38,4 +40,99 @@ impl DataAndMnemonics {
             codecs: smallvec![mnemonic1, mnemonic2],
         }
     }
+    /// Adds a mnemonic to the front of the chain, e.g. recording the Python value encoding
+    /// ahead of whatever data codec chain was applied on top of it.
+    pub fn prepend(mut self, mnemonic: CodecMnemonic) -> Self {
+        self.codecs.insert(0, mnemonic);
+        self
+    }
+}
+
+/// Writes `value` as an unsigned LEB128 varint (the encoding Protocol Buffers uses): 7 bits of
+/// payload per byte, continuation indicated by the high bit. Used for compact self-describing
+/// binary headers, since most of the values we frame this way (chain lengths, byte counts) are
+/// small in practice but shouldn't be hard-capped to a fixed width.
+pub(crate) fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+/// Reads an unsigned LEB128 varint from the start of `data`, returning the decoded value and
+/// the number of bytes it occupied. Bounds-checked: truncated input or a value wider than 64
+/// bits is reported as an error rather than panicking, since this runs on bytes that round
+/// tripped through SQLite and should be treated as untrusted on the way back in.
+pub(crate) fn read_varint(data: &[u8]) -> PyResult<(u64, usize)> {
+    let mut value: u64 = 0;
+    let mut shift = 0u32;
+    for (i, &byte) in data.iter().enumerate() {
+        if shift >= 64 {
+            return Err(PyErr::new::<PyValueError, _>("Varint is too long"));
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+        shift += 7;
+    }
+    Err(PyErr::new::<PyValueError, _>("Truncated varint"))
+}
+
+/// On-disk format version for `encode_codecs_blob`/`decode_codecs_blob`, stored as the header's
+/// first byte. Scope note: this header only frames the chain length and the mnemonic sequence,
+/// not per-codec parameters (zstd level, shuffle `element_width`) — those already travel inline
+/// in each codec's own payload (`ShuffleCodec`/`BitShuffleCodec` write `element_width` as the
+/// first payload byte; zstd frames are self-describing and don't need the level at decode time),
+/// so there's no parameter to hoist into this header without duplicating it. Decode also doesn't
+/// split into a validating pre-pass plus an infallible hot loop; each step stays bounds-checked,
+/// which keeps this header's own format simple. Both are a deliberate reduction in scope from a
+/// more ambitious parameterized-frame design.
+///
+/// Bump this whenever the header layout changes, and keep `decode_codecs_blob` rejecting any
+/// other version with a clear error: a `codecs` column written before this header format existed
+/// was just the raw mnemonic bytes with no version/length prefix at all, and would otherwise be
+/// silently misparsed (an arbitrary leading mnemonic byte read back as a bogus chain length)
+/// rather than rejected. There is no in-place migration for such a column; it must be re-written
+/// (e.g. read with the version of talsi that wrote it, then `set`/`set_many`'d back) before a
+/// build with this header format can read it.
+pub(crate) const CODECS_BLOB_FORMAT_VERSION: u8 = 1;
+
+/// Serializes a codec chain as a compact self-describing header: a format version byte, a
+/// varint giving the chain length, followed by that many mnemonic bytes. Self-describing
+/// because, unlike a bare mnemonic-per-byte blob, the header carries its own version and length
+/// rather than relying on the surrounding column to report the exact byte count.
+pub(crate) fn encode_codecs_blob(codecs: &CodecsBlob) -> Vec<u8> {
+    let mut out = Vec::with_capacity(2 + codecs.len());
+    out.push(CODECS_BLOB_FORMAT_VERSION);
+    write_varint(&mut out, codecs.len() as u64);
+    out.extend_from_slice(codecs.as_slice());
+    out
+}
+
+/// Parses a codec chain header produced by `encode_codecs_blob`, rejecting any other format
+/// version up front and bounds-checking the declared chain length against the bytes actually
+/// present before trusting it.
+pub(crate) fn decode_codecs_blob(data: &[u8]) -> PyResult<CodecsBlob> {
+    let (&version, rest) = data
+        .split_first()
+        .ok_or_else(|| PyErr::new::<PyValueError, _>("Empty codec chain header"))?;
+    if version != CODECS_BLOB_FORMAT_VERSION {
+        return Err(PyErr::new::<PyValueError, _>(format!(
+            "Unsupported codec chain header version {version} (expected {CODECS_BLOB_FORMAT_VERSION}); \
+             this row was written by an incompatible version of talsi and must be re-written \
+             (read it back with the version that wrote it, then set/set_many it again) before \
+             it can be read here"
+        )));
+    }
+    let (len, header_len) = read_varint(rest)?;
+    let mnemonics = rest
+        .get(header_len..header_len + len as usize)
+        .ok_or_else(|| PyErr::new::<PyValueError, _>("Truncated codec chain"))?;
+    Ok(CodecsBlob::from_slice(mnemonics))
 }
@@ -0,0 +1,42 @@
+use crate::data_codecs::DataToDataCodec;
+use crate::typ::DataAndMnemonic;
+use pyo3::PyResult;
+use std::io::Write;
+use tracing::instrument;
+use xz2::write::{XzDecoder, XzEncoder};
+
+pub(crate) struct XzCodec {
+    level: u32,
+}
+
+impl XzCodec {
+    pub fn new_default() -> Self {
+        XzCodec { level: 6 }
+    }
+    pub fn new(level: u32) -> Self {
+        XzCodec { level }
+    }
+}
+
+impl DataToDataCodec for XzCodec {
+    #[instrument(name = "xz_encode", skip_all)]
+    fn encode(&self, data: &[u8]) -> PyResult<DataAndMnemonic> {
+        let mut wtr = XzEncoder::new(Vec::with_capacity(data.len() / 2), self.level);
+        wtr.write_all(data)?;
+        let compressed = wtr.finish()?;
+        Ok(DataAndMnemonic {
+            data: compressed,
+            codec: Self::MNEMONIC,
+        })
+    }
+
+    #[instrument(name = "xz_decode", skip_all)]
+    fn decode(&self, data: &[u8]) -> PyResult<Vec<u8>> {
+        let mut wtr = XzDecoder::new(Vec::new());
+        wtr.write_all(data)?;
+        let decompressed = wtr.finish()?;
+        Ok(decompressed)
+    }
+
+    const MNEMONIC: u8 = b'x';
+}
@@ -4,15 +4,16 @@ use crate::utils::to_talsi_error;
 use pyo3::exceptions::PyValueError;
 use pyo3::{PyErr, PyResult};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use tracing::instrument;
-use zstd::bulk::Compressor;
+use zstd::bulk::{Compressor, Decompressor};
 
 const MAX_ZSTD_LEVEL: usize = 22; // There is no const API to get this
 
 type CompressorsRefCell = RefCell<[Option<Compressor<'static>>; MAX_ZSTD_LEVEL + 1]>;
 
 thread_local! {
-    static ZSTD_ENCODERS: CompressorsRefCell = RefCell::new([const { None }; MAX_ZSTD_LEVEL + 1]);
+    static ZSTD_ENCODERS: CompressorsRefCell = const { RefCell::new([const { None }; MAX_ZSTD_LEVEL + 1]) };
 }
 
 pub(crate) struct ZstdCodec {
@@ -73,3 +74,119 @@ impl DataToDataCodec for ZstdCodec {
 
     const MNEMONIC: u8 = b'z';
 }
+
+/// Distinct mnemonic for zstd frames compressed against a trained dictionary. Decoding these
+/// requires the dictionary bytes, which `decode_from_data_and_mnemonic` has no way to look up,
+/// so `Storage` special-cases this mnemonic and resolves the dictionary itself before calling
+/// `decode_with_dictionary`.
+pub(crate) const DICT_MNEMONIC: u8 = b'Z';
+
+/// Dictionary-keyed compressors/decompressors, analogous to `ZSTD_ENCODERS` above but keyed by
+/// (level, dict id) / dict id since dictionaries are arbitrarily many and not known up front.
+type DictEncodersRefCell = RefCell<HashMap<(i32, u32), Compressor<'static>>>;
+type DictDecodersRefCell = RefCell<HashMap<u32, Decompressor<'static>>>;
+
+thread_local! {
+    static ZSTD_DICT_ENCODERS: DictEncodersRefCell = RefCell::new(HashMap::new());
+    static ZSTD_DICT_DECODERS: DictDecodersRefCell = RefCell::new(HashMap::new());
+}
+
+/// Trains a zstd dictionary from a sample of existing values, capped at `max_size` bytes.
+pub fn train_dictionary(samples: &[Vec<u8>], max_size: usize) -> PyResult<Vec<u8>> {
+    zstd::dict::from_samples(samples, max_size).map_err(|e| {
+        PyErr::new::<PyValueError, _>(format!("Zstd dictionary training failed: {}", e))
+    })
+}
+
+/// Compresses `data` against `dict` (identified by `dict_id`) at the given level, prefixing the
+/// frame with a small header (dict id + original length) so `decode_with_dictionary` can find
+/// the right dictionary and allocate exactly enough space to decompress into.
+pub fn encode_with_dictionary(
+    data: &[u8],
+    level: i32,
+    dict_id: u32,
+    dict: &[u8],
+) -> PyResult<DataAndMnemonic> {
+    let compressed = ZSTD_DICT_ENCODERS
+        .try_with(|encoders| -> PyResult<Vec<u8>> {
+            let mut encoders = encoders.borrow_mut();
+            if let std::collections::hash_map::Entry::Vacant(e) = encoders.entry((level, dict_id))
+            {
+                let compressor = Compressor::with_dictionary(level, dict).map_err(|e| {
+                    PyErr::new::<PyValueError, _>(format!(
+                        "Failed to create Zstd dictionary encoder: {}",
+                        e
+                    ))
+                })?;
+                e.insert(compressor);
+            }
+            encoders
+                .get_mut(&(level, dict_id))
+                .unwrap()
+                .compress(data)
+                .map_err(|e| {
+                    PyErr::new::<PyValueError, _>(format!(
+                        "Zstd dictionary compression failed: {}",
+                        e
+                    ))
+                })
+        })
+        .map_err(to_talsi_error)??;
+
+    let mut framed = Vec::with_capacity(DICT_HEADER_LEN + compressed.len());
+    framed.extend_from_slice(&dict_id.to_le_bytes());
+    framed.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    framed.extend_from_slice(&compressed);
+    Ok(DataAndMnemonic {
+        data: framed,
+        codec: DICT_MNEMONIC,
+    })
+}
+
+const DICT_HEADER_LEN: usize = 4 + 8;
+
+/// Splits a dictionary-compressed frame (see `encode_with_dictionary`) into the dictionary id,
+/// the original (decompressed) length, and the zstd payload.
+pub fn split_dictionary_frame(data: &[u8]) -> PyResult<(u32, usize, &[u8])> {
+    if data.len() < DICT_HEADER_LEN {
+        return Err(PyErr::new::<PyValueError, _>(
+            "Truncated Zstd dictionary frame",
+        ));
+    }
+    let dict_id = u32::from_le_bytes(data[0..4].try_into().unwrap());
+    let original_len = u64::from_le_bytes(data[4..12].try_into().unwrap()) as usize;
+    Ok((dict_id, original_len, &data[DICT_HEADER_LEN..]))
+}
+
+/// Decompresses a dictionary-compressed payload given the resolved dictionary bytes.
+pub fn decode_with_dictionary(
+    payload: &[u8],
+    dict_id: u32,
+    original_len: usize,
+    dict: &[u8],
+) -> PyResult<Vec<u8>> {
+    ZSTD_DICT_DECODERS
+        .try_with(|decoders| -> PyResult<Vec<u8>> {
+            let mut decoders = decoders.borrow_mut();
+            if let std::collections::hash_map::Entry::Vacant(e) = decoders.entry(dict_id) {
+                let decompressor = Decompressor::with_dictionary(dict).map_err(|e| {
+                    PyErr::new::<PyValueError, _>(format!(
+                        "Failed to create Zstd dictionary decoder: {}",
+                        e
+                    ))
+                })?;
+                e.insert(decompressor);
+            }
+            decoders
+                .get_mut(&dict_id)
+                .unwrap()
+                .decompress(payload, original_len)
+                .map_err(|e| {
+                    PyErr::new::<PyValueError, _>(format!(
+                        "Zstd dictionary decompression failed: {}",
+                        e
+                    ))
+                })
+        })
+        .map_err(to_talsi_error)?
+}